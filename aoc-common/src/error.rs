@@ -0,0 +1,266 @@
+use std::fmt::Display;
+
+/// A position in the puzzle input.
+///
+/// Both the line and the column are 1-based, and the column is counted in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    /// Find the location of the byte `offset` in `input`.
+    ///
+    /// Offsets past the end of the input are clamped to the end.
+    pub fn from_offset(input: &str, offset: usize) -> Self {
+        let mut offset = offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        let column = before[line_start..].chars().count() + 1;
+
+        Self { line, column }
+    }
+
+    /// Find the location of `fragment` in `input`.
+    ///
+    /// `fragment` is expected to be a subslice of `input`, such as the remaining input returned by
+    /// a parser or a trimmed line. Fragments from elsewhere are reported at the start of the input.
+    pub fn locate(input: &str, fragment: &str) -> Self {
+        let start = input.as_ptr() as usize;
+        let offset = (fragment.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|offset| *offset <= input.len())
+            .unwrap_or(0);
+
+        Self::from_offset(input, offset)
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Everything that can go wrong while solving a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// There was nothing to solve.
+    EmptyInput,
+    /// A character which doesn't fit the puzzle format.
+    UnexpectedChar {
+        location: Location,
+        found: char,
+        expected: &'static str,
+    },
+    /// The input ended while more was expected.
+    UnexpectedEnd {
+        location: Location,
+        expected: &'static str,
+    },
+    /// Something that should have been a number, but couldn't be parsed as one.
+    InvalidNumber { location: Location, text: String },
+    /// The input is well-formed piece by piece, but doesn't fit together.
+    Malformed { location: Location, reason: String },
+    /// The input was parsed, but there is no answer to be found.
+    NoSolution { reason: String },
+}
+
+impl SolveError {
+    /// Report that the input stopped matching the expected format at `remainder`.
+    ///
+    /// `remainder` must be a subslice of `input`, typically the remaining input of a parser.
+    pub fn unexpected(input: &str, remainder: &str, expected: &'static str) -> Self {
+        let location = Location::locate(input, remainder);
+        match remainder.chars().next() {
+            Some(found) => SolveError::UnexpectedChar {
+                location,
+                found,
+                expected,
+            },
+            None => SolveError::UnexpectedEnd { location, expected },
+        }
+    }
+
+    /// Report that `text`, a subslice of `input`, is not a valid number.
+    pub fn invalid_number(input: &str, text: &str) -> Self {
+        SolveError::InvalidNumber {
+            location: Location::locate(input, text),
+            text: text.to_string(),
+        }
+    }
+
+    /// Report that the input doesn't fit together at `fragment`, a subslice of `input`.
+    pub fn malformed(input: &str, fragment: &str, reason: impl Into<String>) -> Self {
+        SolveError::Malformed {
+            location: Location::locate(input, fragment),
+            reason: reason.into(),
+        }
+    }
+
+    /// Move the location down by `lines`, for errors found in a piece of a larger input which
+    /// starts on line `lines + 1`, such as a single line read from a stream.
+    pub fn shifted(mut self, lines: usize) -> Self {
+        match &mut self {
+            SolveError::UnexpectedChar { location, .. }
+            | SolveError::UnexpectedEnd { location, .. }
+            | SolveError::InvalidNumber { location, .. }
+            | SolveError::Malformed { location, .. } => location.line += lines,
+            SolveError::EmptyInput | SolveError::NoSolution { .. } => {}
+        }
+        self
+    }
+
+    /// The location of the offending input, if the error points at one.
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            SolveError::UnexpectedChar { location, .. }
+            | SolveError::UnexpectedEnd { location, .. }
+            | SolveError::InvalidNumber { location, .. }
+            | SolveError::Malformed { location, .. } => Some(*location),
+            SolveError::EmptyInput | SolveError::NoSolution { .. } => None,
+        }
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::EmptyInput => write!(f, "the input is empty"),
+            SolveError::UnexpectedChar {
+                location,
+                found,
+                expected,
+            } => write!(f, "{location}: expected {expected}, found {found:?}"),
+            SolveError::UnexpectedEnd { location, expected } => {
+                write!(
+                    f,
+                    "{location}: expected {expected}, found the end of the input"
+                )
+            }
+            SolveError::InvalidNumber { location, text } => {
+                write!(f, "{location}: {text:?} is not a valid number")
+            }
+            SolveError::Malformed { location, reason } => write!(f, "{location}: {reason}"),
+            SolveError::NoSolution { reason } => write!(f, "no solution: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Split a grid puzzle into its rows.
+///
+/// Trailing empty lines are ignored. The grid must have at least one row, and every row must have
+/// the same number of characters as the first.
+pub fn grid_rows(input: &str) -> Result<Vec<&str>, SolveError> {
+    let mut rows: Vec<&str> = input.lines().collect();
+    while rows.last().is_some_and(|row| row.is_empty()) {
+        rows.pop();
+    }
+
+    let width = match rows.first() {
+        Some(first) => first.chars().count(),
+        None => return Err(SolveError::EmptyInput),
+    };
+
+    for row in rows.iter() {
+        let row_width = row.chars().count();
+        if row_width != width {
+            return Err(SolveError::malformed(
+                input,
+                row,
+                format!("expected a row of {width} characters, found {row_width}"),
+            ));
+        }
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("abc", 0, Location::new(1, 1))]
+    #[case("abc", 2, Location::new(1, 3))]
+    #[case("abc\ndef", 4, Location::new(2, 1))]
+    #[case("abc\ndef", 6, Location::new(2, 3))]
+    #[case("abc\ndef", 100, Location::new(2, 4))]
+    #[case("øl\nx", 2, Location::new(1, 2))]
+    fn test_location_from_offset(
+        #[case] input: &str,
+        #[case] offset: usize,
+        #[case] expected: Location,
+    ) {
+        assert_eq!(Location::from_offset(input, offset), expected);
+    }
+
+    #[test]
+    fn locate_finds_subslices() {
+        let input = "47|53\n97|x3\n";
+        let line = input.lines().nth(1).unwrap();
+
+        assert_eq!(Location::locate(input, line), Location::new(2, 1));
+        assert_eq!(Location::locate(input, &line[3..]), Location::new(2, 4));
+    }
+
+    #[rstest]
+    #[case("1|", "x2", SolveError::UnexpectedChar { location: Location::new(1, 3), found: 'x', expected: "a number" })]
+    #[case("1|", "", SolveError::UnexpectedEnd { location: Location::new(1, 3), expected: "a number" })]
+    fn test_unexpected(#[case] prefix: &str, #[case] rest: &str, #[case] expected: SolveError) {
+        let input = format!("{prefix}{rest}");
+        let remainder = &input[prefix.len()..];
+
+        assert_eq!(
+            SolveError::unexpected(&input, remainder, "a number"),
+            expected
+        );
+    }
+
+    #[test]
+    fn shifted_moves_the_location_down() {
+        let line = "97|x3";
+        let err = SolveError::unexpected(line, &line[3..], "a number").shifted(4);
+
+        assert_eq!(err.location(), Some(Location::new(5, 4)));
+        assert_eq!(SolveError::EmptyInput.shifted(4), SolveError::EmptyInput);
+    }
+
+    #[rstest]
+    #[case("...\n...\n...", Ok(vec!["...", "...", "..."]))]
+    #[case("...\n...\n\n", Ok(vec!["...", "..."]))]
+    #[case("", Err(SolveError::EmptyInput))]
+    #[case("\n\n", Err(SolveError::EmptyInput))]
+    #[case("...\n..\n...", Err(SolveError::Malformed {
+        location: Location::new(2, 1),
+        reason: "expected a row of 3 characters, found 2".to_string(),
+    }))]
+    fn test_grid_rows(#[case] input: &str, #[case] expected: Result<Vec<&str>, SolveError>) {
+        assert_eq!(grid_rows(input), expected);
+    }
+
+    #[test]
+    fn errors_render_their_location() {
+        let input = "1 2\n3 x";
+        let err = SolveError::invalid_number(input, &input[6..]);
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 3: \"x\" is not a valid number"
+        );
+        assert_eq!(err.location(), Some(Location::new(2, 3)));
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt::Display;
+
+mod error;
+
+pub use error::{grid_rows, Location, SolveError};
+
+/// One of the two parts of a daily puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl TryFrom<u8> for Part {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            _ => Err(value),
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// The solution to a single day.
+///
+/// Every day crate implements this for a unit struct, which lets tooling drive all days the same
+/// way instead of relying on the `part1::process`/`part2::process` naming convention.
+pub trait Solution {
+    /// The answer produced by both parts.
+    type Answer: Display;
+
+    /// The day of the puzzle.
+    fn day(&self) -> u8;
+
+    /// The puzzle text, as stored in `daily_description.txt`.
+    fn description(&self) -> &'static str;
+
+    /// The title of the puzzle, taken from the header of the description.
+    ///
+    /// Returns an empty string if the description has no header.
+    fn title(&self) -> &'static str {
+        parse_title(self.description()).unwrap_or_default()
+    }
+
+    fn try_part1(&self, input: &str) -> Result<Self::Answer, SolveError>;
+
+    fn try_part2(&self, input: &str) -> Result<Self::Answer, SolveError>;
+
+    /// Solve part 1.
+    ///
+    /// # Panics
+    /// Panics if the input can't be solved. Use [`Solution::try_part1`] to handle this instead.
+    fn part1(&self, input: &str) -> Self::Answer {
+        self.try_part1(input)
+            .unwrap_or_else(|e| panic!("Failed to solve part 1: {e}"))
+    }
+
+    /// Solve part 2.
+    ///
+    /// # Panics
+    /// Panics if the input can't be solved. Use [`Solution::try_part2`] to handle this instead.
+    fn part2(&self, input: &str) -> Self::Answer {
+        self.try_part2(input)
+            .unwrap_or_else(|e| panic!("Failed to solve part 2: {e}"))
+    }
+}
+
+/// Object safe version of [`Solution`], with the answer rendered to a string.
+///
+/// This is implemented for every [`Solution`], so solutions with different answer types can be
+/// stored side by side.
+pub trait AnySolution: Sync {
+    fn day(&self) -> u8;
+
+    fn title(&self) -> &'static str;
+
+    fn solve(&self, part: Part, input: &str) -> Result<String, SolveError>;
+}
+
+impl<S> AnySolution for S
+where
+    S: Solution + Sync,
+{
+    fn day(&self) -> u8 {
+        Solution::day(self)
+    }
+
+    fn title(&self) -> &'static str {
+        Solution::title(self)
+    }
+
+    fn solve(&self, part: Part, input: &str) -> Result<String, SolveError> {
+        let answer = match part {
+            Part::One => self.try_part1(input)?,
+            Part::Two => self.try_part2(input)?,
+        };
+
+        Ok(answer.to_string())
+    }
+}
+
+/// Extract the title from a puzzle description.
+///
+/// The description starts with a header on the form `--- Day 7: Bridge Repair ---`, possibly
+/// preceded by blank lines.
+pub fn parse_title(description: &str) -> Option<&str> {
+    let header = description
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+
+    let header = header.strip_prefix("--- Day ")?.strip_suffix(" ---")?;
+    let (_day, title) = header.split_once(": ")?;
+
+    Some(title.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(
+        "--- Day 7: Bridge Repair ---\n\nThe Historians take you...",
+        Some("Bridge Repair")
+    )]
+    #[case("\n--- Day 3: Mull It Over ---\n", Some("Mull It Over"))]
+    #[case("--- Day 11: Plutonian Pebbles ---", Some("Plutonian Pebbles"))]
+    #[case("", None)]
+    #[case("Some text without a header.", None)]
+    fn test_parse_title(#[case] description: &str, #[case] expected: Option<&str>) {
+        assert_eq!(parse_title(description), expected);
+    }
+
+    #[rstest]
+    #[case(1, Ok(Part::One))]
+    #[case(2, Ok(Part::Two))]
+    #[case(0, Err(0))]
+    #[case(3, Err(3))]
+    fn test_part_from_u8(#[case] value: u8, #[case] expected: Result<Part, u8>) {
+        assert_eq!(Part::try_from(value), expected);
+    }
+
+    struct Example;
+
+    impl Solution for Example {
+        type Answer = u64;
+
+        fn day(&self) -> u8 {
+            42
+        }
+
+        fn description(&self) -> &'static str {
+            "--- Day 42: Example ---"
+        }
+
+        fn try_part1(&self, input: &str) -> Result<u64, SolveError> {
+            Ok(input.len() as u64)
+        }
+
+        fn try_part2(&self, input: &str) -> Result<u64, SolveError> {
+            if input.is_empty() {
+                return Err(SolveError::EmptyInput);
+            }
+            Ok(input.len() as u64 * 2)
+        }
+    }
+
+    #[test]
+    fn any_solution_renders_the_answer() {
+        let solution: &dyn AnySolution = &Example;
+
+        assert_eq!(solution.day(), 42);
+        assert_eq!(solution.title(), "Example");
+        assert_eq!(solution.solve(Part::One, "abc"), Ok("3".to_string()));
+        assert_eq!(solution.solve(Part::Two, "abc"), Ok("6".to_string()));
+        assert_eq!(solution.solve(Part::Two, ""), Err(SolveError::EmptyInput));
+    }
+}
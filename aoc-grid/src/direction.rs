@@ -0,0 +1,123 @@
+use glam::IVec2;
+
+/// A direction on the grid.
+///
+/// `Up` is towards the first row, and `Left` is towards the first column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// The four directions along the rows and columns, clockwise from `Up`.
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// The four diagonal directions, clockwise from `UpRight`.
+    pub const DIAGONAL: [Direction; 4] = [
+        Direction::UpRight,
+        Direction::DownRight,
+        Direction::DownLeft,
+        Direction::UpLeft,
+    ];
+
+    /// All eight directions, clockwise from `Up`.
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    /// The step taken when moving one cell in this direction.
+    pub fn offset(self) -> IVec2 {
+        match self {
+            Direction::Up => IVec2::new(0, -1),
+            Direction::UpRight => IVec2::new(1, -1),
+            Direction::Right => IVec2::new(1, 0),
+            Direction::DownRight => IVec2::new(1, 1),
+            Direction::Down => IVec2::new(0, 1),
+            Direction::DownLeft => IVec2::new(-1, 1),
+            Direction::Left => IVec2::new(-1, 0),
+            Direction::UpLeft => IVec2::new(-1, -1),
+        }
+    }
+
+    /// Whether this is one of the four diagonal directions.
+    pub fn is_diagonal(self) -> bool {
+        Self::DIAGONAL.contains(&self)
+    }
+
+    /// Rotate by `eighths` of a full turn clockwise. Negative values rotate counter-clockwise.
+    pub fn rotate(self, eighths: i32) -> Direction {
+        let idx = Self::ALL
+            .iter()
+            .position(|dir| *dir == self)
+            .expect("Every direction is in the list.");
+        Self::ALL[(idx as i32 + eighths).rem_euclid(8) as usize]
+    }
+
+    /// Turn 90 degrees clockwise.
+    pub fn turn_right(self) -> Direction {
+        self.rotate(2)
+    }
+
+    /// Turn 90 degrees counter-clockwise.
+    pub fn turn_left(self) -> Direction {
+        self.rotate(-2)
+    }
+
+    /// Turn around.
+    pub fn reverse(self) -> Direction {
+        self.rotate(4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Direction::Up, Direction::Right)]
+    #[case(Direction::Right, Direction::Down)]
+    #[case(Direction::Down, Direction::Left)]
+    #[case(Direction::Left, Direction::Up)]
+    #[case(Direction::UpLeft, Direction::UpRight)]
+    fn test_turn_right(#[case] direction: Direction, #[case] expected: Direction) {
+        assert_eq!(direction.turn_right(), expected);
+        assert_eq!(expected.turn_left(), direction);
+    }
+
+    #[test]
+    fn reversing_negates_the_offset() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.reverse().offset(), -direction.offset());
+            assert_eq!(direction.reverse().reverse(), direction);
+        }
+    }
+
+    #[test]
+    fn diagonals_move_along_both_axes() {
+        for direction in Direction::ALL {
+            let offset = direction.offset();
+            assert_eq!(direction.is_diagonal(), offset.x != 0 && offset.y != 0);
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use aoc_common::{grid_rows, SolveError};
+pub use glam::IVec2;
+
+mod direction;
+
+pub use direction::Direction;
+
+/// A rectangular grid of cells.
+///
+/// Positions are `IVec2`s where `x` is the column and `y` is the row, both starting at 0 in the
+/// upper left corner. Positions outside the grid are valid to compute with, but there is nothing
+/// stored there.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid where every cell holds `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Parse a grid from text, converting each character with `parse_cell`.
+    ///
+    /// If `parse_cell` rejects a character, the error points at it and says the cell was expected
+    /// to be `expected`.
+    pub fn parse_with(
+        input: &str,
+        expected: &'static str,
+        mut parse_cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, SolveError> {
+        let rows = grid_rows(input)?;
+        let width = rows[0].chars().count();
+        let height = rows.len();
+
+        let mut cells = Vec::with_capacity(width * height);
+        for row in rows {
+            for (idx, ch) in row.char_indices() {
+                let cell = parse_cell(ch)
+                    .ok_or_else(|| SolveError::unexpected(input, &row[idx..], expected))?;
+                cells.push(cell);
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The width and height of the grid.
+    pub fn size(&self) -> IVec2 {
+        IVec2::new(self.width as i32, self.height as i32)
+    }
+
+    /// Whether `pos` is inside the grid.
+    pub fn contains(&self, pos: IVec2) -> bool {
+        (0..self.width as i32).contains(&pos.x) && (0..self.height as i32).contains(&pos.y)
+    }
+
+    fn index_of(&self, pos: IVec2) -> Option<usize> {
+        self.contains(pos)
+            .then(|| pos.y as usize * self.width + pos.x as usize)
+    }
+
+    pub fn get(&self, pos: IVec2) -> Option<&T> {
+        self.index_of(pos).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, pos: IVec2) -> Option<&mut T> {
+        self.index_of(pos).map(|idx| &mut self.cells[idx])
+    }
+
+    /// Every position in the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = IVec2> {
+        let width = self.width as i32;
+        (0..self.height as i32).flat_map(move |y| (0..width).map(move |x| IVec2::new(x, y)))
+    }
+
+    /// Every cell in the grid along with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The rows of the grid, from the top.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The position of the first cell, row by row, which satisfies `predicate`.
+    pub fn find(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<IVec2> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+
+    /// The neighbours of `pos` in the given `directions` which are inside the grid.
+    pub fn neighbours(
+        &self,
+        pos: IVec2,
+        directions: &'static [Direction],
+    ) -> impl Iterator<Item = IVec2> + '_ {
+        directions
+            .iter()
+            .map(move |direction| pos + direction.offset())
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    /// The up to four neighbours of `pos` sharing an edge with it.
+    pub fn neighbours4(&self, pos: IVec2) -> impl Iterator<Item = IVec2> + '_ {
+        self.neighbours(pos, &Direction::ORTHOGONAL)
+    }
+
+    /// The up to eight neighbours of `pos`, including the diagonal ones.
+    pub fn neighbours8(&self, pos: IVec2) -> impl Iterator<Item = IVec2> + '_ {
+        self.neighbours(pos, &Direction::ALL)
+    }
+
+    /// Walk from `start` in `direction` until leaving the grid.
+    ///
+    /// `start` itself is the first position yielded, as long as it is inside the grid.
+    pub fn ray(&self, start: IVec2, direction: Direction) -> impl Iterator<Item = IVec2> + '_ {
+        let step = direction.offset();
+        std::iter::successors(Some(start), move |pos| Some(*pos + step))
+            .take_while(|pos| self.contains(*pos))
+    }
+
+    /// Convert every cell, keeping the shape of the grid.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Rotate the grid 90 degrees clockwise.
+    pub fn rotate_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        // The new row `y` is the old column `y`, read from the bottom.
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for y in 0..self.width {
+            for x in 0..self.height {
+                cells.push(self.cells[(self.height - 1 - x) * self.width + y].clone());
+            }
+        }
+
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+
+    /// Rotate the grid 90 degrees counter-clockwise.
+    pub fn rotate_counter_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        // The new row `y` is the old column `width - 1 - y`, read from the top.
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for y in 0..self.width {
+            for x in 0..self.height {
+                cells.push(self.cells[x * self.width + (self.width - 1 - y)].clone());
+            }
+        }
+
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+
+    /// Render the grid as text, one line per row, with `render_cell` choosing each character.
+    pub fn render_with(&self, mut render_cell: impl FnMut(IVec2, &T) -> char) -> String {
+        let mut rendered = String::with_capacity((self.width + 1) * self.height);
+        for (pos, cell) in self.iter() {
+            if pos.x == 0 && pos.y > 0 {
+                rendered.push('\n');
+            }
+            rendered.push(render_cell(pos, cell));
+        }
+        rendered
+    }
+}
+
+impl Grid<char> {
+    /// Parse a grid of characters from text.
+    pub fn parse(input: &str) -> Result<Self, SolveError> {
+        Self::parse_with(input, "any character", Some)
+    }
+}
+
+impl<T> Index<IVec2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: IVec2) -> &Self::Output {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos} is outside the grid of size {}", self.size()))
+    }
+}
+
+impl<T> IndexMut<IVec2> for Grid<T> {
+    fn index_mut(&mut self, pos: IVec2) -> &mut Self::Output {
+        let size = self.size();
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos} is outside the grid of size {size}"))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, row) in self.rows().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::Location;
+    use rstest::rstest;
+
+    use super::*;
+
+    const EXAMPLE: &str = "abc\ndef";
+
+    #[test]
+    fn parse_keeps_rows_and_columns() {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[IVec2::new(0, 0)], 'a');
+        assert_eq!(grid[IVec2::new(2, 0)], 'c');
+        assert_eq!(grid[IVec2::new(1, 1)], 'e');
+        assert_eq!(grid.to_string(), EXAMPLE);
+    }
+
+    #[rstest]
+    #[case("", SolveError::EmptyInput)]
+    #[case("12\n3x\n", SolveError::UnexpectedChar {
+        location: Location::new(2, 2),
+        found: 'x',
+        expected: "a digit",
+    })]
+    fn parse_with_reports_invalid_cells(#[case] input: &str, #[case] expected: SolveError) {
+        assert_eq!(
+            Grid::parse_with(input, "a digit", |ch| ch.to_digit(10)),
+            Err(expected)
+        );
+    }
+
+    #[rstest]
+    #[case(IVec2::new(0, 0), true)]
+    #[case(IVec2::new(2, 1), true)]
+    #[case(IVec2::new(3, 1), false)]
+    #[case(IVec2::new(2, 2), false)]
+    #[case(IVec2::new(-1, 0), false)]
+    #[case(IVec2::new(0, -1), false)]
+    fn test_contains(#[case] pos: IVec2, #[case] expected: bool) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        assert_eq!(grid.contains(pos), expected);
+        assert_eq!(grid.get(pos).is_some(), expected);
+    }
+
+    #[rstest]
+    #[case(IVec2::new(0, 0), vec![IVec2::new(1, 0), IVec2::new(0, 1)])]
+    #[case(IVec2::new(1, 1), vec![IVec2::new(1, 0), IVec2::new(2, 1), IVec2::new(0, 1)])]
+    fn test_neighbours4(#[case] pos: IVec2, #[case] expected: Vec<IVec2>) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        assert_eq!(grid.neighbours4(pos).collect::<Vec<_>>(), expected);
+    }
+
+    #[rstest]
+    #[case(IVec2::new(0, 0), 3)]
+    #[case(IVec2::new(1, 0), 5)]
+    #[case(IVec2::new(1, 1), 5)]
+    fn test_neighbours8(#[case] pos: IVec2, #[case] expected: usize) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        assert_eq!(grid.neighbours8(pos).count(), expected);
+    }
+
+    #[rstest]
+    #[case(IVec2::new(0, 0), Direction::Right, "abc")]
+    #[case(IVec2::new(2, 1), Direction::Left, "fed")]
+    #[case(IVec2::new(0, 0), Direction::DownRight, "ae")]
+    #[case(IVec2::new(1, 1), Direction::Up, "eb")]
+    #[case(IVec2::new(1, 1), Direction::Down, "e")]
+    #[case(IVec2::new(5, 5), Direction::Up, "")]
+    fn test_ray(#[case] start: IVec2, #[case] direction: Direction, #[case] expected: &str) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        let cells: String = grid.ray(start, direction).map(|pos| grid[pos]).collect();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc");
+        assert_eq!(grid.rotate_counter_clockwise().to_string(), "cf\nbe\nad");
+        assert_eq!(grid.rotate_clockwise().rotate_counter_clockwise(), grid);
+    }
+
+    #[test]
+    fn test_render_with() {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        let rendered = grid.render_with(|pos, ch| if pos.x == pos.y { *ch } else { '.' });
+        assert_eq!(rendered, "a..\n.e.");
+    }
+
+    #[test]
+    fn test_find_and_map() {
+        let grid = Grid::parse_with("0123\n4567", "a digit", |ch| ch.to_digit(10)).unwrap();
+
+        assert_eq!(grid.find(|height| *height == 5), Some(IVec2::new(1, 1)));
+        assert_eq!(grid.find(|height| *height == 9), None);
+        assert_eq!(grid.map(|height| height % 2).to_string(), "0101\n0101");
+    }
+}
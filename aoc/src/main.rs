@@ -0,0 +1,325 @@
+use std::{fs, path::PathBuf, process::ExitCode, time::Instant};
+
+use aoc_common::{AnySolution, Part};
+use clap::{Parser, Subcommand};
+use day_7::{
+    evaluation::Evaluation,
+    number::Number,
+    operator::OperatorSet,
+    report::{report, Report, Show},
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "aoc", about = "Run the Advent of Code 2024 solutions")]
+struct Args {
+    /// Log what the solutions are doing to stderr.
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the solution for a single day.
+    Run {
+        /// The day to run.
+        #[arg(short, long)]
+        day: u8,
+
+        /// The part to run. Both parts are run if omitted.
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// Path to the puzzle input.
+        /// Defaults to `day-<day>/input<part>.txt` in the workspace.
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+
+    /// Check each line of a day 7 calibration file, showing the operators which make it true.
+    Calibrate {
+        /// Path to the calibration file.
+        /// Defaults to `day-7/input2.txt` in the workspace.
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+
+        /// The operators to choose from, separated by spaces.
+        #[arg(short, long, default_value = "+ * ||")]
+        operators: String,
+
+        /// The order the operators are worked out in: `left-to-right`, `precedence` or
+        /// `parentheses`.
+        #[arg(short, long, default_value = "left-to-right")]
+        evaluation: String,
+
+        /// Show every solution instead of just one.
+        #[arg(short, long)]
+        all: bool,
+
+        /// Work with 128-bit numbers, for equations which don't fit in 64 bits.
+        #[arg(short, long)]
+        wide: bool,
+    },
+
+    /// List every solved day.
+    List,
+}
+
+/// Every solved day, in order.
+static SOLUTIONS: [&dyn AnySolution; 11] = [
+    &day_1::Day1,
+    &day_2::Day2,
+    &day_3::Day3,
+    &day_4::Day4,
+    &day_5::Day5,
+    &day_6::Day6,
+    &day_7::Day7,
+    &day_8::Day8,
+    &day_9::Day9,
+    &day_10::Day10,
+    &day_11::Day11,
+];
+
+/// Writes log messages from the solutions to stderr.
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Look up the solution for the given day.
+fn solution(day: u8) -> Option<&'static dyn AnySolution> {
+    SOLUTIONS
+        .iter()
+        .find(|solution| solution.day() == day)
+        .copied()
+}
+
+/// The input file shipped with each day crate.
+fn default_input(day: u8, part: Part) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(format!("day-{day}"))
+        .join(format!("input{part}.txt"))
+}
+
+fn run(day: u8, part: Part, input: Option<&PathBuf>) -> Result<(), String> {
+    let solution = solution(day).ok_or(format!("There is no solution for day {day}."))?;
+
+    let path = input.cloned().unwrap_or_else(|| default_input(day, part));
+    let file =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+    let start = Instant::now();
+    let result = solution
+        .solve(part, &file)
+        .map_err(|e| format!("Failed to solve day {day} part {part}: {e}"))?;
+    let elapsed = start.elapsed();
+
+    println!("day {day} part {part}: {result} ({elapsed:?})");
+
+    Ok(())
+}
+
+/// Check every equation in a calibration file, and write out what was found.
+fn calibrate(
+    file: &str,
+    operators: &str,
+    evaluation: &str,
+    all: bool,
+    wide: bool,
+) -> Result<String, String> {
+    if wide {
+        calibrate_with::<u128>(file, operators, evaluation, all)
+    } else {
+        calibrate_with::<u64>(file, operators, evaluation, all)
+    }
+}
+
+fn calibrate_with<N: Number + Into<u128>>(
+    file: &str,
+    operators: &str,
+    evaluation: &str,
+    all: bool,
+) -> Result<String, String> {
+    let operators: OperatorSet<N> = operators.parse().map_err(|e| format!("{e}"))?;
+    let evaluation: Evaluation = evaluation.parse().map_err(|e| format!("{e}"))?;
+    let show = if all { Show::All } else { Show::One };
+    let reports = report(file, &operators, evaluation, show)
+        .map_err(|e| format!("Failed to read the calibration file: {e}"))?;
+
+    let solvable: Vec<&Report<N>> = reports.iter().filter(|r| r.is_solvable()).collect();
+    let total = solvable
+        .iter()
+        .try_fold(0_u128, |total, r| {
+            total.checked_add(r.equation.result.into())
+        })
+        .ok_or("The total doesn't fit in 128 bits")?;
+
+    let mut output = String::new();
+    for report in reports.iter() {
+        output.push_str(&format!("{report}\n"));
+    }
+    output.push_str(&format!(
+        "{} of {} equations can be made true, adding up to {total}",
+        solvable.len(),
+        reports.len()
+    ));
+
+    Ok(output)
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    // Logging is opt-in, so the solutions stay quiet unless asked.
+    if args.verbose {
+        log::set_logger(&LOGGER).expect("No other logger has been set.");
+        log::set_max_level(log::LevelFilter::Debug);
+    }
+
+    match args.command {
+        Command::Run { day, part, input } => {
+            let parts = match part {
+                Some(part) => vec![Part::try_from(part).expect("clap limits the part to 1 or 2")],
+                None => vec![Part::One, Part::Two],
+            };
+
+            for part in parts {
+                if let Err(e) = run(day, part, input.as_ref()) {
+                    eprintln!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        Command::Calibrate {
+            input,
+            operators,
+            evaluation,
+            all,
+            wide,
+        } => {
+            let path = input.unwrap_or_else(|| default_input(7, Part::Two));
+            let output = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))
+                .and_then(|file| calibrate(&file, &operators, &evaluation, all, wide));
+            match output {
+                Ok(output) => println!("{output}"),
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        Command::List => {
+            for solution in SOLUTIONS {
+                println!("day {:>2}: {}", solution.day(), solution.title());
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(1, Part::One, "3   4\n4   3\n2   5\n1   3\n3   9\n3   3", "11")]
+    #[case(1, Part::Two, "3   4\n4   3\n2   5\n1   3\n3   9\n3   3", "31")]
+    #[case(7, Part::One, "190: 10 19\n3267: 81 40 27\n292: 11 6 16 20", "3749")]
+    fn solution_dispatches_to_the_day_crate(
+        #[case] day: u8,
+        #[case] part: Part,
+        #[case] input: &str,
+        #[case] expected: &str,
+    ) {
+        let solution = solution(day).expect("The day should have a solution.");
+        assert_eq!(solution.solve(part, input), Ok(expected.to_string()));
+    }
+
+    #[test]
+    fn solution_reports_malformed_input() {
+        let solution = solution(1).expect("Day 1 should have a solution.");
+        let err = solution
+            .solve(Part::One, "3   4\n4   x")
+            .expect_err("The input is malformed.");
+
+        assert_eq!(err.location(), Some(aoc_common::Location::new(2, 5)));
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(12)]
+    fn solution_returns_none_for_unknown_days(#[case] day: u8) {
+        assert!(solution(day).is_none());
+    }
+
+    #[test]
+    fn calibrate_shows_the_operators_for_each_line() {
+        let output = calibrate(
+            "190: 10 19\n83: 17 5\n156: 15 6",
+            "+ * ||",
+            "left-to-right",
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            output,
+            "line 1: 190: 10 19 has 1 solution
+  190 = 10 * 19
+line 2: 83: 17 5 has no solutions
+line 3: 156: 15 6 has 1 solution
+  156 = 15 || 6
+2 of 3 equations can be made true, adding up to 346"
+        );
+        assert_eq!(
+            calibrate("190: 10 19", "+ %", "left-to-right", false, false),
+            Err("unknown operator \"%\"".to_string())
+        );
+        assert_eq!(
+            calibrate("3267: 81 40 27", "+ *", "precedence", true, false).unwrap(),
+            "line 1: 3267: 81 40 27 has 1 solution
+  3267 = 81 * 40 + 27
+1 of 1 equations can be made true, adding up to 3267"
+        );
+    }
+
+    #[test]
+    fn calibrate_can_use_wide_numbers() {
+        let file = "36893488147419103230: 18446744073709551615 2";
+
+        assert!(calibrate(file, "+ *", "left-to-right", false, false).is_err());
+        assert_eq!(
+            calibrate(file, "+ *", "left-to-right", false, true).unwrap(),
+            "line 1: 36893488147419103230: 18446744073709551615 2 has 1 solution
+  36893488147419103230 = 18446744073709551615 * 2
+1 of 1 equations can be made true, adding up to 36893488147419103230"
+        );
+    }
+
+    #[test]
+    fn solutions_are_listed_in_order_with_titles() {
+        for (idx, solution) in SOLUTIONS.iter().enumerate() {
+            assert_eq!(solution.day() as usize, idx + 1);
+            assert!(!solution.title().is_empty());
+        }
+    }
+}
@@ -1,5 +1,11 @@
+use aoc_common::SolveError;
+
 pub fn process(input: &str) -> usize {
-    0
+    try_process(input).expect("The input should be valid.")
+}
+
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
+    Ok(0)
 }
 
 #[cfg(test)]
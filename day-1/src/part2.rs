@@ -1,25 +1,26 @@
+use aoc_common::SolveError;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 pub fn process(input: &str) -> usize {
+    try_process(input).expect("The input should be two columns of numbers.")
+}
+
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
     // Split the input into lines.
     let lines = input.lines();
 
-    // Strip whitespace at each end.
-    let lines: Vec<&str> = lines.map(|line| line.trim()).collect();
+    // Strip whitespace at each end, and skip empty lines.
+    let lines: Vec<&str> = lines
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
 
     // Split each line on whitespace, and save the two numbers in two separate lists.
-    let (first_num_list, second_num_list): (Vec<usize>, Vec<usize>) = lines
+    let pairs: Vec<(usize, usize)> = lines
         .par_iter()
-        .map(|line| {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            let (first_num_str, second_num_str) = parts.split_at(1);
-
-            (
-                first_num_str.first().unwrap().parse::<usize>().unwrap(),
-                second_num_str.first().unwrap().parse::<usize>().unwrap(),
-            )
-        })
-        .unzip();
+        .map(|line| parse_line(input, line))
+        .collect::<Result<_, _>>()?;
+    let (first_num_list, second_num_list): (Vec<usize>, Vec<usize>) = pairs.into_iter().unzip();
 
     // For each number in the first list, find the number of times it appears in the second list.
     let num_count: Vec<(usize, usize)> = first_num_list
@@ -37,11 +38,37 @@ pub fn process(input: &str) -> usize {
     // Multiply the first number with the number of times it appears in the second list.
     let sum = num_count.par_iter().map(|(num, count)| num * count).sum();
 
-    sum
+    Ok(sum)
+}
+
+/// Parse a line of two whitespace separated numbers.
+///
+/// `line` must be a subslice of `input`, which is used to locate errors.
+fn parse_line(input: &str, line: &str) -> Result<(usize, usize), SolveError> {
+    let mut parts = line.split_whitespace();
+
+    let mut parse_next = || {
+        let part = parts
+            .next()
+            .ok_or_else(|| SolveError::unexpected(input, &line[line.len()..], "a number"))?;
+        part.parse::<usize>()
+            .map_err(|_| SolveError::invalid_number(input, part))
+    };
+    let first = parse_next()?;
+    let second = parse_next()?;
+
+    if let Some(extra) = parts.next() {
+        return Err(SolveError::unexpected(input, extra, "the end of the line"));
+    }
+
+    Ok((first, second))
 }
 
 #[cfg(test)]
 mod tests {
+    use aoc_common::Location;
+    use rstest::rstest;
+
     use super::*;
 
     #[test]
@@ -55,4 +82,12 @@ mod tests {
         dbg!(&input);
         assert_eq!(process(input), 31);
     }
+
+    #[rstest]
+    #[case("3   4\n4   x\n", SolveError::InvalidNumber { location: Location::new(2, 5), text: "x".to_string() })]
+    #[case("3   4\n4\n", SolveError::UnexpectedEnd { location: Location::new(2, 2), expected: "a number" })]
+    #[case("3   4\n4   3   2\n", SolveError::UnexpectedChar { location: Location::new(2, 9), found: '2', expected: "the end of the line" })]
+    fn try_process_reports_malformed_lines(#[case] input: &str, #[case] expected: SolveError) {
+        assert_eq!(try_process(input), Err(expected));
+    }
 }
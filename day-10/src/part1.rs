@@ -1,46 +1,29 @@
 use std::collections::HashSet;
 
-use glam::UVec2;
+use aoc_common::SolveError;
+use aoc_grid::{Grid, IVec2};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 struct Position {
-    position: UVec2,
+    position: IVec2,
     height: u32,
 }
 
-fn parse_mountain(input: &str) -> Vec<Vec<Position>> {
-    let mut mountain = vec![];
-    for (y, line) in input.lines().enumerate() {
-        if line.is_empty() {
-            continue;
-        }
-
-        let mut mountain_line = vec![];
-        for (x, c) in line.chars().enumerate() {
-            let height = c.to_digit(10).map_or_else(|| 0x45, |d| d);
-
-            mountain_line.push(Position {
-                position: UVec2::new(x as u32, y as u32),
-                height,
-            });
-        }
-        mountain.push(mountain_line);
-    }
-    mountain
+fn parse_mountain(input: &str) -> Result<Grid<u32>, SolveError> {
+    Grid::parse_with(input, "a digit or '.'", |c| match c {
+        // Impassable spots are marked with '.', and get a height we can never step up to.
+        '.' => Some(0x45),
+        _ => c.to_digit(10),
+    })
 }
 
-fn find_trailheads(mountain: &Vec<Vec<Position>>) -> Vec<Position> {
+fn find_trailheads(mountain: &Grid<u32>) -> Vec<Position> {
     mountain
         .iter()
-        .enumerate()
-        .flat_map(|(mount_idx, mount_line)| {
-            mount_line.iter().enumerate().filter_map(|(pos_idx, pos)| {
-                if pos.height == 0 {
-                    Some(*pos)
-                } else {
-                    None
-                }
-            })
+        .filter(|(_, height)| **height == 0)
+        .map(|(position, height)| Position {
+            position,
+            height: *height,
         })
         .collect()
 }
@@ -48,149 +31,52 @@ fn find_trailheads(mountain: &Vec<Vec<Position>>) -> Vec<Position> {
 fn traverse_mountain(
     current_position: &Position,
     path_taken: &[Position],
-    mountain: &Vec<Vec<Position>>,
+    mountain: &Grid<u32>,
 ) -> Vec<Vec<Position>> {
     // Find positions around "self" where the height is one higher.
     // For each of these, start a new recursive traversal from that position, but make sure to
     // append the current function to "path_taken" before passing it in.
     // If one of the paths leads to a 9, add it to the path and return that as a solution.
 
-    let mountain_dimensions = (
-        mountain
-            .first()
-            .expect("We should never have empty lists")
-            .len(),
-        mountain.len(),
-    );
     let one_step_up = current_position.height + 1;
 
-    // Get coordinates for direction.
-    let up_coords = match (
-        current_position.position.x,
-        current_position.position.y.checked_sub(1),
-    ) {
-        (x, Some(y)) => Some((x, y)),
-        _ => None,
-    };
-    let right_coords =
-        if (0..mountain_dimensions.0).contains(&((current_position.position.x + 1) as usize)) {
-            Some((current_position.position.x + 1, current_position.position.y))
-        } else {
-            None
-        };
-    let down_coords =
-        if (0..mountain_dimensions.1).contains(&((current_position.position.y + 1) as usize)) {
-            Some((current_position.position.x, current_position.position.y + 1))
-        } else {
-            None
-        };
-    let left_coords = match (
-        current_position.position.x.checked_sub(1),
-        current_position.position.y,
-    ) {
-        (Some(x), y) => Some((x, y)),
-        _ => None,
-    };
+    let mut path_taken = path_taken.to_vec();
+    path_taken.push(*current_position);
 
-    // Now check each direction if it contains a slot with a height of H+1 or 9.
+    // Check each direction if it contains a slot with a height of H+1 or 9.
     // If so, either start another run or return a list of the current path.
-    let up_lists = if let Some((x, y)) = up_coords {
-        let next_position = mountain[y as usize][x as usize];
-        let next_height = next_position.height;
-
-        let mut path_taken = path_taken.to_vec();
-        path_taken.push(*current_position);
-
-        if next_height == 9 && one_step_up == 9 {
-            path_taken.push(next_position);
-            // println!("Moving up: Found path to top: {:?}", path_taken);
-            vec![path_taken]
-        } else if next_height == one_step_up {
-            traverse_mountain(&next_position, &path_taken, mountain)
-        } else {
-            vec![]
-        }
-    } else {
-        vec![]
-    };
-
-    let right_lists = if let Some((x, y)) = right_coords {
-        let next_position = mountain[y as usize][x as usize];
-        let next_height = next_position.height;
-
-        let mut path_taken = path_taken.to_vec();
-        path_taken.push(*current_position);
-
-        if next_height == 9 && one_step_up == 9 {
-            path_taken.push(next_position);
-            // println!("Moving right: Found path to top: {:?}", path_taken);
-            vec![path_taken]
-        } else if next_height == one_step_up {
-            traverse_mountain(&next_position, &path_taken, mountain)
-        } else {
-            vec![]
-        }
-    } else {
-        vec![]
-    };
-
-    let down_lists = if let Some((x, y)) = down_coords {
-        let next_position = mountain[y as usize][x as usize];
-        let next_height = next_position.height;
-
-        let mut path_taken = path_taken.to_vec();
-        path_taken.push(*current_position);
-
-        if next_height == 9 && one_step_up == 9 {
-            path_taken.push(next_position);
-            // println!("Moving down: Found path to top: {:?}", path_taken);
-            vec![path_taken]
-        } else if next_height == one_step_up {
-            traverse_mountain(&next_position, &path_taken, mountain)
-        } else {
-            vec![]
-        }
-    } else {
-        vec![]
-    };
-
-    let left_lists = if let Some((x, y)) = left_coords {
-        let next_position = mountain[y as usize][x as usize];
-        let next_height = next_position.height;
-
-        let mut path_taken = path_taken.to_vec();
-        path_taken.push(*current_position);
-
-        if next_height == 9 && one_step_up == 9 {
-            path_taken.push(next_position);
-            // println!("Moving left: Found path to top: {:?}", path_taken);
-            vec![path_taken]
-        } else if next_height == one_step_up {
-            traverse_mountain(&next_position, &path_taken, mountain)
-        } else {
-            vec![]
-        }
-    } else {
-        vec![]
-    };
-
-    let mut return_vec = vec![];
-    return_vec.extend(up_lists);
-    return_vec.extend(right_lists);
-    return_vec.extend(down_lists);
-    return_vec.extend(left_lists);
-
-    return_vec
+    mountain
+        .neighbours4(current_position.position)
+        .flat_map(|position| {
+            let next_position = Position {
+                position,
+                height: mountain[position],
+            };
+
+            if next_position.height == 9 && one_step_up == 9 {
+                let mut path_taken = path_taken.clone();
+                path_taken.push(next_position);
+                vec![path_taken]
+            } else if next_position.height == one_step_up {
+                traverse_mountain(&next_position, &path_taken, mountain)
+            } else {
+                vec![]
+            }
+        })
+        .collect()
 }
 
 pub fn process(input: &str) -> usize {
-    let mountain = parse_mountain(input);
+    try_process(input).expect("The input should be a rectangular map of heights.")
+}
+
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
+    let mountain = parse_mountain(input)?;
     let trailheads = find_trailheads(&mountain);
 
     let paths: Vec<Vec<Position>> = trailheads
         .into_iter()
-        .enumerate()
-        .flat_map(|(idx, trailhead)| traverse_mountain(&trailhead, &[], &mountain))
+        .flat_map(|trailhead| traverse_mountain(&trailhead, &[], &mountain))
         .collect();
 
     // Just count the number of unique ends we've found.
@@ -203,7 +89,7 @@ pub fn process(input: &str) -> usize {
         unique_tops.insert(start_end);
     });
 
-    unique_tops.len()
+    Ok(unique_tops.len())
 }
 
 #[cfg(test)]
@@ -259,9 +145,24 @@ mod tests {
 ...8..3
 ...9..2
 .....01",
-        2
+        3
     )]
     fn test_process(#[case] input: &str, #[case] expected: usize) {
         assert_eq!(process(input), expected);
     }
+
+    #[rstest]
+    #[case("", SolveError::EmptyInput)]
+    #[case("0123\n1x34\n", SolveError::UnexpectedChar {
+        location: aoc_common::Location::new(2, 2),
+        found: 'x',
+        expected: "a digit or '.'",
+    })]
+    #[case("0123\n123\n", SolveError::Malformed {
+        location: aoc_common::Location::new(2, 1),
+        reason: "expected a row of 4 characters, found 3".to_string(),
+    })]
+    fn try_process_reports_invalid_maps(#[case] input: &str, #[case] expected: SolveError) {
+        assert_eq!(try_process(input), Err(expected));
+    }
 }
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use aoc_common::SolveError;
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 struct Stone {
@@ -87,7 +87,7 @@ fn blink(
                 // Since we apply rules, then blink on the new stones, we perform a depth-first process.
                 // This should ideally avoid endless RAM usage since we will hopefully avoid keeping all
                 // stones in memory at once.
-                let new_stones = Stone::apply_rules(&[stone.clone()]);
+                let new_stones = Stone::apply_rules(std::slice::from_ref(stone));
                 let num = blink(num_blink - 1, &new_stones, known_combinations);
                 known_combinations.insert((num_blink, stone.clone()), num);
                 num
@@ -98,16 +98,35 @@ fn blink(
     sum
 }
 
+/// Parse the first non-empty line as a list of stones.
+fn parse_stones(input: &str) -> Result<Vec<Stone>, SolveError> {
+    let line = input
+        .lines()
+        .find(|l| !l.is_empty())
+        .ok_or(SolveError::EmptyInput)?;
+
+    line.split_whitespace()
+        .map(|s| {
+            // Re-format the number to strip any leading zeroes.
+            s.parse::<usize>()
+                .map(|num| Stone::new(&num.to_string()))
+                .map_err(|_| SolveError::invalid_number(input, s))
+        })
+        .collect()
+}
+
 pub fn process(input: &str) -> usize {
-    let input: Vec<&str> = input.lines().filter(|l| !l.is_empty()).collect();
-    let strings: Vec<&str> = input.first().unwrap().split(' ').collect();
-    let stones: Vec<Stone> = strings.iter().map(|s| Stone::new(s)).collect();
+    try_process(input).expect("The input should be a line of numbers.")
+}
+
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
+    let stones = parse_stones(input)?;
     let mut stone_rule_lookuptable = HashMap::new();
 
     // Task says 75 blinks.
     let num_blinks = 75;
 
-    blink(num_blinks, &stones, &mut stone_rule_lookuptable)
+    Ok(blink(num_blinks, &stones, &mut stone_rule_lookuptable))
 }
 
 #[cfg(test)]
@@ -122,6 +141,18 @@ mod tests {
     //     assert_eq!(process(input), 0);
     // }
 
+    #[rstest]
+    #[case("125 17", Ok(vec![Stone::new("125"), Stone::new("17")]))]
+    #[case("\n0125  17\n", Ok(vec![Stone::new("125"), Stone::new("17")]))]
+    #[case("", Err(SolveError::EmptyInput))]
+    #[case("125 1x7", Err(SolveError::InvalidNumber {
+        location: aoc_common::Location::new(1, 5),
+        text: "1x7".to_string(),
+    }))]
+    fn test_parse_stones(#[case] input: &str, #[case] expected: Result<Vec<Stone>, SolveError>) {
+        assert_eq!(parse_stones(input), expected);
+    }
+
     #[rstest]
     #[case(
         &["125", "17"],
@@ -199,4 +230,3 @@ mod tests {
         assert_eq!(res, expected);
     }
 }
-
@@ -1,2 +1,27 @@
+use aoc_common::{Solution, SolveError};
+
 pub mod part1;
 pub mod part2;
+pub mod safety;
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Answer = usize;
+
+    fn day(&self) -> u8 {
+        2
+    }
+
+    fn description(&self) -> &'static str {
+        include_str!("../daily_description.txt")
+    }
+
+    fn try_part1(&self, input: &str) -> Result<usize, SolveError> {
+        part1::try_process(input)
+    }
+
+    fn try_part2(&self, input: &str) -> Result<usize, SolveError> {
+        part2::try_process(input)
+    }
+}
@@ -1,27 +1,23 @@
 use std::collections::HashSet;
 
+use aoc_common::SolveError;
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 
-const SAFE_LIMIT: usize = 3;
+use crate::safety::{assess, SafetyPolicy};
 
-#[derive(Debug)]
-enum Direction {
-    Ascending,
-    Descending,
+pub fn process(input: &str) -> usize {
+    try_process(input).expect("Each line should be a list of numbers.")
 }
 
-pub fn process(input: &str) -> usize {
-    let numbers = get_numbers_from_input(input);
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
+    let numbers = get_numbers_from_input(input)?;
 
-    let sum = numbers
-        .iter()
-        .filter_map(|nums| is_report_safe(nums, 2).then_some(1))
-        .count();
+    let (_, summary) = assess(&numbers, &SafetyPolicy::DAMPENED);
 
-    sum
+    Ok(summary.total_safe())
 }
 
-fn get_numbers_from_input(input: &str) -> Vec<Vec<usize>> {
+fn get_numbers_from_input(input: &str) -> Result<Vec<Vec<usize>>, SolveError> {
     // Split the input into lines.
     let lines = input.lines();
 
@@ -35,20 +31,20 @@ fn get_numbers_from_input(input: &str) -> Vec<Vec<usize>> {
         .collect();
 
     // Convert each line into a vector of numbers.
-    let numbers: Vec<Vec<usize>> = lines
+    lines
         .par_iter()
         .map(|line| {
             // Split each line into subsections.
             let parts: Vec<&str> = line.split_whitespace().collect();
-            let numbers: Vec<usize> = parts
+            parts
                 .par_iter()
-                .map(|num| num.parse::<usize>().unwrap())
-                .collect();
-            numbers
+                .map(|num| {
+                    num.parse::<usize>()
+                        .map_err(|_| SolveError::invalid_number(input, num))
+                })
+                .collect()
         })
-        .collect();
-
-    numbers
+        .collect()
 }
 
 #[allow(dead_code)]
@@ -59,123 +55,12 @@ fn number_of_duplicates_in_list(list: &[usize]) -> usize {
         .count()
 }
 
-fn is_report_safe(numbers: &[usize], attempts_remaining: usize) -> bool {
-    if attempts_remaining == 0 {
-        return false;
-    }
-
-    // We should now allow _one_ fail, which should be removed, but the rules must still apply
-    // overall.
-    let mut direction: Option<Direction> = None;
-
-    let mut first_number_index = 0;
-    let mut second_number_index = 1;
-
-    let mut first_num = numbers.get(first_number_index);
-    let mut second_num = numbers.get(second_number_index);
-
-    while first_num.is_some() && second_num.is_some() {
-        let first = first_num.unwrap();
-        let second = second_num.unwrap();
-
-        // Indicates if anything in this loop iteration has been flagged as unsafe.
-        // This shall trigger a retry with each of the current numbers removed.
-        let mut iteration_safe = true;
-
-        // First check for duplicates.
-        // if number_of_duplicates_in_list(numbers) > 0 {
-        //     println!("List contained duplicates.");
-        //     iteration_safe = false;
-        // }
-
-        // Determine the direction of the numbers.
-
-        if iteration_safe && direction.is_none() {
-            direction = match first.cmp(second) {
-                std::cmp::Ordering::Less => Some(Direction::Ascending),
-                std::cmp::Ordering::Greater => Some(Direction::Descending),
-                std::cmp::Ordering::Equal => {
-                    println!("Determining direction failed due to duplicates.");
-                    iteration_safe = false;
-                    None
-                }
-            };
-        }
-
-        // Check if the difference between the two numbers is greater than the safe limit.
-        if iteration_safe {
-            match direction {
-                Some(Direction::Ascending) => {
-                    if second <= first {
-                        // We were ascending, but the second number was smaller or equal to the first.
-                        // Unsafe.
-                        println!("Ascending, but second number was smaller or equal to the first.");
-                        iteration_safe = false;
-                    } else if usize::abs_diff(*first, *second) > SAFE_LIMIT {
-                        // The difference between the two numbers is greater than the safe limit.
-                        println!(
-                            "Difference between the two numbers was greater than the safe limit."
-                        );
-                        iteration_safe = false;
-                    }
-                }
-                Some(Direction::Descending) => {
-                    if second >= first {
-                        // We were descending, but the second number was greater or equal to the first.
-                        // Unsafe.
-                        println!(
-                            "Descending, but second number was greater or equal to the first."
-                        );
-                        iteration_safe = false;
-                    } else if usize::abs_diff(*first, *second) > SAFE_LIMIT {
-                        // The difference between the two numbers is greater than the safe limit.
-                        println!(
-                            "Difference between the two numbers was greater than the safe limit."
-                        );
-                        iteration_safe = false;
-                    }
-                }
-                _ => panic!("Direction was not determined."),
-            }
-        }
-
-        if !iteration_safe && (attempts_remaining - 1 > 0) {
-            // Try once again with each number in the list removed to check if we can bounce back
-            // from a single issue.
-
-            println!("Retrying with original list: {:?}", numbers);
-
-            let any_success = (0..numbers.len())
-                .collect::<Vec<usize>>()
-                .iter()
-                .any(|index| {
-                    let mut vec_with_num_removed: Vec<usize> = numbers.to_vec();
-                    vec_with_num_removed.remove(*index);
-
-                    // Only allow a single attempt. No infinite recursion here!
-                    is_report_safe(&vec_with_num_removed, 1)
-                });
-
-            return any_success;
-        } else if !iteration_safe {
-            return false;
-        }
-
-        first_number_index += 1;
-        second_number_index += 1;
-
-        // Re-aqcuire the numbers.
-        // Easier to handle the loop like this.
-        first_num = numbers.get(first_number_index);
-        second_num = numbers.get(second_number_index);
-    }
-
-    true
-}
-
 #[cfg(test)]
 mod tests {
+    use aoc_common::Location;
+
     use super::*;
+    use crate::safety::is_report_safe;
 
     #[test]
     fn test_process() {
@@ -208,63 +93,75 @@ mod tests {
         assert_eq!(list_has_duplicates, 1);
     }
 
+    #[test]
+    fn try_process_reports_invalid_numbers() {
+        let input = "7 6 4 2 1\n1 2 7 8 9\n9 7 -6 2 1\n";
+        assert_eq!(
+            try_process(input),
+            Err(SolveError::InvalidNumber {
+                location: Location::new(3, 5),
+                text: "-6".to_string()
+            })
+        );
+    }
+
     #[test]
     fn is_report_safe_returns_true_when_passed_7_6_4_2_1() {
-        let numbers = vec![vec![7, 6, 4, 2, 1]];
-        assert!(is_report_safe(&numbers[0], 2));
+        let numbers = [vec![7, 6, 4, 2, 1]];
+        assert!(is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 
     #[test]
     fn is_report_safe_returns_false_when_passed_1_2_7_8_9() {
-        let numbers = vec![vec![1, 2, 7, 8, 9]];
-        assert!(!is_report_safe(&numbers[0], 2));
+        let numbers = [vec![1, 2, 7, 8, 9]];
+        assert!(!is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 
     #[test]
     fn is_report_safe_returns_false_when_passed_9_7_6_2_1() {
-        let numbers = vec![vec![9, 7, 6, 2, 1]];
-        assert!(!is_report_safe(&numbers[0], 2));
+        let numbers = [vec![9, 7, 6, 2, 1]];
+        assert!(!is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 
     #[test]
     fn is_report_safe_returns_true_when_passed_1_3_2_4_5() {
-        let numbers = vec![vec![1, 3, 2, 4, 5]];
-        assert!(is_report_safe(&numbers[0], 2));
+        let numbers = [vec![1, 3, 2, 4, 5]];
+        assert!(is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 
     #[test]
     fn is_report_safe_returns_true_when_passed_8_6_4_4_1() {
-        let numbers = vec![vec![8, 6, 4, 4, 1]];
-        assert!(is_report_safe(&numbers[0], 2));
+        let numbers = [vec![8, 6, 4, 4, 1]];
+        assert!(is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 
     #[test]
     fn is_report_safe_returns_true_when_passed_1_3_6_7_9() {
-        let numbers = vec![vec![1, 3, 6, 7, 9]];
-        assert!(is_report_safe(&numbers[0], 2));
+        let numbers = [vec![1, 3, 6, 7, 9]];
+        assert!(is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 
     #[test]
     fn is_report_safe_returns_true_when_passed_20_21_24_25_27_29_27() {
-        let numbers = vec![vec![20, 21, 24, 25, 27, 29, 27]];
-        assert!(is_report_safe(&numbers[0], 2));
+        let numbers = [vec![20, 21, 24, 25, 27, 29, 27]];
+        assert!(is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 
     #[test]
     fn is_report_safe_returns_false_when_passed_6_5_7_8_11_11() {
-        let numbers = vec![vec![6, 5, 7, 8, 11, 11]];
-        assert!(!is_report_safe(&numbers[0], 2));
+        let numbers = [vec![6, 5, 7, 8, 11, 11]];
+        assert!(!is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 
     #[test]
     fn is_report_safe_returns_false_when_passed_17_10_9_6_6_2() {
-        let numbers = vec![vec![17, 10, 9, 6, 6, 2]];
-        assert!(!is_report_safe(&numbers[0], 2));
+        let numbers = [vec![17, 10, 9, 6, 6, 2]];
+        assert!(!is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 
     #[test]
     fn is_report_safe_returns_true_when_passed_10_8_12_14_15_17() {
-        let numbers = vec![vec![10, 8, 12, 14, 15, 17]];
-        assert!(is_report_safe(&numbers[0], 2));
+        let numbers = [vec![10, 8, 12, 14, 15, 17]];
+        assert!(is_report_safe(&numbers[0], &SafetyPolicy::DAMPENED));
     }
 }
@@ -0,0 +1,577 @@
+//! Deciding whether a report is safe, under a configurable set of tolerances.
+
+use std::{cmp::Ordering, collections::HashMap, fmt::Display};
+
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+/// Which way the levels of a report are allowed to go.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Trend {
+    /// Every step must go up.
+    Ascending,
+    /// Every step must go down.
+    Descending,
+    /// Either way, as long as every step goes the same way as the first.
+    #[default]
+    Either,
+}
+
+/// The tolerances a report has to stay within to be safe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SafetyPolicy {
+    /// The smallest allowed difference between neighbouring levels.
+    pub min_step: usize,
+    /// The largest allowed difference between neighbouring levels.
+    pub max_step: usize,
+    pub trend: Trend,
+    /// How many levels may be removed from a report to make it safe.
+    pub removable_levels: usize,
+    /// Whether neighbouring levels may be equal. Equal levels are then allowed regardless of
+    /// `min_step`, and don't decide which way the report goes.
+    pub allow_equal_neighbours: bool,
+}
+
+impl SafetyPolicy {
+    /// The rules from part 1: levels go up or down by 1 to 3 at each step.
+    pub const STRICT: Self = Self {
+        min_step: 1,
+        max_step: 3,
+        trend: Trend::Either,
+        removable_levels: 0,
+        allow_equal_neighbours: false,
+    };
+
+    /// The rules from part 2: as [`SafetyPolicy::STRICT`], but the Problem Dampener may remove a
+    /// single level.
+    pub const DAMPENED: Self = Self {
+        removable_levels: 1,
+        ..Self::STRICT
+    };
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        Self::STRICT
+    }
+}
+
+/// Whether the report is safe under `policy`, possibly after removing some levels.
+pub fn is_report_safe(levels: &[usize], policy: &SafetyPolicy) -> bool {
+    dampen(levels, policy).is_some()
+}
+
+/// Find the fewest levels to remove to make the report safe under `policy`.
+///
+/// Returns the indices of the levels to remove, in increasing order, or `None` if it takes more
+/// than `policy.removable_levels` removals. A report which is already safe needs no removals.
+/// When there are several ways to remove as few levels, the later levels are kept.
+///
+/// For `n` levels, `k` removable levels and `s` allowed differences between neighbouring levels,
+/// this runs in `O(n * min(k, s))` time. With the puzzle's steps of 1 to 3, that is linear in the
+/// length of the report for any number of removals.
+pub fn dampen(levels: &[usize], policy: &SafetyPolicy) -> Option<Vec<usize>> {
+    let trends: &[Ordering] = match policy.trend {
+        Trend::Ascending => &[Ordering::Less],
+        Trend::Descending => &[Ordering::Greater],
+        // Equal neighbours don't decide the trend, so going either way is the same as going up or
+        // going down all the way.
+        Trend::Either => &[Ordering::Less, Ordering::Greater],
+    };
+
+    trends
+        .iter()
+        .filter_map(|trend| dampen_with_trend(levels, policy, *trend))
+        .min_by_key(|removed| removed.len())
+}
+
+/// Like [`dampen`], with every step going the way of `trend`.
+///
+/// Removing the fewest levels is keeping the most, so this finds the longest run of levels which
+/// follow the rules with each level next to the one kept before it.
+fn dampen_with_trend(
+    levels: &[usize],
+    policy: &SafetyPolicy,
+    trend: Ordering,
+) -> Option<Vec<usize>> {
+    let len = levels.len();
+    let k = policy.removable_levels;
+    if len == 0 {
+        return Some(vec![]);
+    }
+
+    let allowed = |first: usize, second: usize| match first.cmp(&second) {
+        Ordering::Equal => policy.allow_equal_neighbours,
+        ordering => {
+            ordering == trend
+                && (policy.min_step..=policy.max_step).contains(&first.abs_diff(second))
+        }
+    };
+    // The levels which may come right before `level`, found by going back by each allowed step.
+    let min_step = policy.min_step.max(1);
+    let steps = policy
+        .max_step
+        .checked_sub(min_step)
+        .map_or(0, |range| range + 1)
+        .saturating_add(policy.allow_equal_neighbours as usize);
+    let before = |level: usize| {
+        (min_step..=policy.max_step)
+            .filter_map(move |step| match trend {
+                Ordering::Less => level.checked_sub(step),
+                _ => level.checked_add(step),
+            })
+            .chain(policy.allow_equal_neighbours.then_some(level))
+    };
+
+    // `kept[i]` is the most levels up to and including `i` which can follow the rules, keeping
+    // level `i`, or 0 if that takes more than `k` removals. `previous[i]` is the level kept right
+    // before it, the earliest one if there are several.
+    let mut kept = vec![0; len];
+    let mut previous: Vec<Option<usize>> = vec![None; len];
+    // The most levels kept so far ending in each value, and the first level ending each such run.
+    let mut most_by_value: HashMap<usize, usize> = HashMap::new();
+    let mut first_by_run: HashMap<(usize, usize), usize> = HashMap::new();
+    for i in 0..len {
+        let (most, earliest) = if steps <= k {
+            // Look up the levels this one can follow by value, however far back they are.
+            let most = before(levels[i])
+                .filter_map(|level| most_by_value.get(&level))
+                .max()
+                .copied();
+            let earliest = most.and_then(|most| {
+                before(levels[i])
+                    .filter_map(|level| first_by_run.get(&(level, most)))
+                    .min()
+                    .copied()
+            });
+            (most, earliest)
+        } else {
+            // Only the last `k + 1` levels can be kept right before this one.
+            let window = i.saturating_sub(k + 1)..i;
+            let follows = |j: &usize| kept[*j] > 0 && allowed(levels[*j], levels[i]);
+            let most = window.clone().filter(follows).map(|j| kept[j]).max();
+            let earliest =
+                most.and_then(|most| window.clone().find(|j| follows(j) && kept[*j] == most));
+            (most, earliest)
+        };
+
+        // Otherwise remove every level before this one.
+        let run = most.map_or(1, |most| most + 1);
+        if i + 1 - run > k {
+            continue;
+        }
+        kept[i] = run;
+        previous[i] = earliest;
+        let longest = most_by_value.entry(levels[i]).or_default();
+        *longest = (*longest).max(run);
+        first_by_run.entry((levels[i], run)).or_insert(i);
+    }
+
+    // Keep the longest run, ending as late as possible.
+    let most = *kept.iter().max()?;
+    if len - most > k {
+        return None;
+    }
+    let last = kept.iter().rposition(|run| *run == most)?;
+
+    let mut is_kept = vec![false; len];
+    let mut level = Some(last);
+    while let Some(idx) = level {
+        is_kept[idx] = true;
+        level = previous[idx];
+    }
+
+    Some((0..len).filter(|idx| !is_kept[*idx]).collect())
+}
+
+/// Why a step between two levels breaks the rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reason {
+    /// The levels are equal, which the policy doesn't allow.
+    EqualNeighbours,
+    /// The step goes the other way from the trend.
+    WrongDirection,
+    /// The levels differ by less than the policy's `min_step`.
+    StepTooSmall { step: usize },
+    /// The levels differ by more than the policy's `max_step`.
+    StepTooLarge { step: usize },
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::EqualNeighbours => write!(f, "it is equal to the level before it"),
+            Reason::WrongDirection => write!(f, "it goes the other way from the levels before it"),
+            Reason::StepTooSmall { step } => {
+                write!(f, "it is only {step} away from the level before it")
+            }
+            Reason::StepTooLarge { step } => {
+                write!(f, "it is {step} away from the level before it")
+            }
+        }
+    }
+}
+
+/// The outcome of checking a single report.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// The report is safe as it is.
+    Safe,
+    /// The report is safe once the levels at these indices are removed.
+    SafeAfterRemoving { indices: Vec<usize> },
+    /// The report can't be made safe. The level at `index` is the first one breaking the rules.
+    Unsafe { index: usize, reason: Reason },
+}
+
+impl Verdict {
+    pub fn is_safe(&self) -> bool {
+        !matches!(self, Verdict::Unsafe { .. })
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Safe => write!(f, "safe"),
+            Verdict::SafeAfterRemoving { indices } => {
+                write!(f, "safe after removing the levels at {indices:?}")
+            }
+            Verdict::Unsafe { index, reason } => {
+                write!(f, "unsafe at level {index}, as {reason}")
+            }
+        }
+    }
+}
+
+/// Check a report under `policy`, explaining why it isn't safe as it is.
+pub fn verdict(levels: &[usize], policy: &SafetyPolicy) -> Verdict {
+    let Some((index, reason)) = first_violation(levels, policy) else {
+        return Verdict::Safe;
+    };
+
+    match dampen(levels, policy) {
+        Some(indices) => Verdict::SafeAfterRemoving { indices },
+        None => Verdict::Unsafe { index, reason },
+    }
+}
+
+/// Find the first level which breaks the rules, without removing any levels.
+///
+/// With [`Trend::Either`], the first step between two different levels decides the trend.
+fn first_violation(levels: &[usize], policy: &SafetyPolicy) -> Option<(usize, Reason)> {
+    let mut trend = match policy.trend {
+        Trend::Ascending => Some(Ordering::Less),
+        Trend::Descending => Some(Ordering::Greater),
+        Trend::Either => None,
+    };
+
+    levels.windows(2).enumerate().find_map(|(idx, pair)| {
+        let (first, second) = (pair[0], pair[1]);
+        let step = first.abs_diff(second);
+        let reason = match first.cmp(&second) {
+            Ordering::Equal if policy.allow_equal_neighbours => None,
+            Ordering::Equal => Some(Reason::EqualNeighbours),
+            ordering if *trend.get_or_insert(ordering) != ordering => Some(Reason::WrongDirection),
+            _ if step < policy.min_step => Some(Reason::StepTooSmall { step }),
+            _ if step > policy.max_step => Some(Reason::StepTooLarge { step }),
+            _ => None,
+        };
+        reason.map(|reason| (idx + 1, reason))
+    })
+}
+
+/// How many reports got each verdict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Summary {
+    pub reports: usize,
+    /// The reports which were safe as they were.
+    pub safe: usize,
+    /// The reports which were safe after removing some levels.
+    pub dampened: usize,
+    pub unsafe_reports: usize,
+    /// The number of levels removed across all dampened reports.
+    pub removed_levels: usize,
+}
+
+impl Summary {
+    /// The reports which are safe, with or without removing levels.
+    pub fn total_safe(&self) -> usize {
+        self.safe + self.dampened
+    }
+}
+
+impl<'a> FromIterator<&'a Verdict> for Summary {
+    fn from_iter<T: IntoIterator<Item = &'a Verdict>>(iter: T) -> Self {
+        let mut summary = Summary::default();
+        for verdict in iter {
+            summary.reports += 1;
+            match verdict {
+                Verdict::Safe => summary.safe += 1,
+                Verdict::SafeAfterRemoving { indices } => {
+                    summary.dampened += 1;
+                    summary.removed_levels += indices.len();
+                }
+                Verdict::Unsafe { .. } => summary.unsafe_reports += 1,
+            }
+        }
+        summary
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} reports: {} safe, {} safe after removing {} levels, {} unsafe",
+            self.reports, self.safe, self.dampened, self.removed_levels, self.unsafe_reports
+        )
+    }
+}
+
+/// Check every report under `policy`, returning the verdicts in the same order as the reports.
+///
+/// Each verdict is logged at the debug level, and the summary at the info level. Nothing is
+/// printed unless the program using this has installed a logger.
+pub fn assess(reports: &[Vec<usize>], policy: &SafetyPolicy) -> (Vec<Verdict>, Summary) {
+    let verdicts: Vec<Verdict> = reports
+        .par_iter()
+        .map(|levels| verdict(levels, policy))
+        .collect();
+
+    for (idx, (levels, verdict)) in reports.iter().zip(verdicts.iter()).enumerate() {
+        log::debug!("report {} {levels:?} is {verdict}", idx + 1);
+    }
+    let summary: Summary = verdicts.iter().collect();
+    log::info!("{summary}");
+
+    (verdicts, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(&[7, 6, 4, 2, 1], true, true)]
+    #[case(&[1, 2, 7, 8, 9], false, false)]
+    #[case(&[9, 7, 6, 2, 1], false, false)]
+    #[case(&[1, 3, 2, 4, 5], false, true)]
+    #[case(&[8, 6, 4, 4, 1], false, true)]
+    #[case(&[1, 3, 6, 7, 9], true, true)]
+    fn test_puzzle_policies(
+        #[case] levels: &[usize],
+        #[case] strict: bool,
+        #[case] dampened: bool,
+    ) {
+        assert_eq!(is_report_safe(levels, &SafetyPolicy::STRICT), strict);
+        assert_eq!(is_report_safe(levels, &SafetyPolicy::DAMPENED), dampened);
+    }
+
+    #[rstest]
+    #[case(SafetyPolicy { trend: Trend::Ascending, ..SafetyPolicy::STRICT }, &[1, 2, 4], true)]
+    #[case(SafetyPolicy { trend: Trend::Ascending, ..SafetyPolicy::STRICT }, &[4, 2, 1], false)]
+    #[case(SafetyPolicy { trend: Trend::Descending, ..SafetyPolicy::STRICT }, &[4, 2, 1], true)]
+    #[case(SafetyPolicy { max_step: 5, ..SafetyPolicy::STRICT }, &[1, 6, 10], true)]
+    #[case(SafetyPolicy { min_step: 2, ..SafetyPolicy::STRICT }, &[1, 3, 4], false)]
+    #[case(SafetyPolicy { allow_equal_neighbours: true, ..SafetyPolicy::STRICT }, &[1, 1, 3, 3], true)]
+    #[case(SafetyPolicy { allow_equal_neighbours: true, ..SafetyPolicy::STRICT }, &[3, 3, 4, 2], false)]
+    #[case(SafetyPolicy { removable_levels: 2, ..SafetyPolicy::STRICT }, &[1, 9, 2, 9, 3], true)]
+    #[case(SafetyPolicy::DAMPENED, &[1, 9, 2, 9, 3], false)]
+    fn test_custom_policies(
+        #[case] policy: SafetyPolicy,
+        #[case] levels: &[usize],
+        #[case] expected: bool,
+    ) {
+        assert_eq!(is_report_safe(levels, &policy), expected);
+    }
+
+    #[rstest]
+    #[case(&[7, 6, 4, 2, 1], 1, Some(vec![]))]
+    #[case(&[1, 3, 2, 4, 5], 1, Some(vec![2]))]
+    #[case(&[8, 6, 4, 4, 1], 1, Some(vec![3]))]
+    #[case(&[1, 2, 7, 8, 9], 1, None)]
+    #[case(&[1, 9, 2, 9, 3], 2, Some(vec![1, 3]))]
+    #[case(&[50, 1, 2, 3, 90], 2, Some(vec![0, 4]))]
+    #[case(&[5, 1], 1, Some(vec![0]))]
+    #[case(&[5, 5, 5], 2, Some(vec![0, 1]))]
+    #[case(&[], 0, Some(vec![]))]
+    fn dampen_returns_the_levels_to_remove(
+        #[case] levels: &[usize],
+        #[case] removable_levels: usize,
+        #[case] expected: Option<Vec<usize>>,
+    ) {
+        let policy = SafetyPolicy {
+            removable_levels,
+            ..SafetyPolicy::STRICT
+        };
+
+        assert_eq!(dampen(levels, &policy), expected);
+    }
+
+    /// Try removing every combination of up to `removable_levels` levels.
+    fn brute_force(levels: &[usize], policy: &SafetyPolicy) -> Option<usize> {
+        let strict = SafetyPolicy {
+            removable_levels: 0,
+            ..*policy
+        };
+        if dampen(levels, &strict).is_some() {
+            return Some(0);
+        }
+        if policy.removable_levels == 0 {
+            return None;
+        }
+        let fewer = SafetyPolicy {
+            removable_levels: policy.removable_levels - 1,
+            ..*policy
+        };
+        (0..levels.len())
+            .filter_map(|idx| {
+                let mut removed = levels.to_vec();
+                removed.remove(idx);
+                brute_force(&removed, &fewer)
+            })
+            .min()
+            .map(|removals| removals + 1)
+    }
+
+    #[rstest]
+    #[case(SafetyPolicy::STRICT)]
+    #[case(SafetyPolicy { allow_equal_neighbours: true, ..SafetyPolicy::STRICT })]
+    #[case(SafetyPolicy { trend: Trend::Descending, min_step: 2, ..SafetyPolicy::STRICT })]
+    fn dampen_agrees_with_brute_force(#[case] policy: SafetyPolicy) {
+        // A small linear congruential generator, so the reports are the same on every run.
+        let mut seed: u64 = 2024;
+        let mut next = move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize
+        };
+
+        for _ in 0..500 {
+            let len = next() % 8;
+            let mut levels = vec![next() % 10 + 30];
+            for _ in 1..len {
+                let step = next() % 7;
+                levels.push(levels[levels.len() - 1] + step - 3);
+            }
+
+            for removable_levels in 0..=3 {
+                let policy = SafetyPolicy {
+                    removable_levels,
+                    ..policy
+                };
+                let removed = dampen(&levels, &policy);
+
+                assert_eq!(
+                    removed.as_ref().map(|removed| removed.len()),
+                    brute_force(&levels, &policy),
+                    "{levels:?} with {removable_levels} removable levels",
+                );
+                if let Some(removed) = removed {
+                    let kept: Vec<usize> = (0..levels.len())
+                        .filter(|idx| !removed.contains(idx))
+                        .map(|idx| levels[idx])
+                        .collect();
+                    assert!(dampen(
+                        &kept,
+                        &SafetyPolicy {
+                            removable_levels: 0,
+                            ..policy
+                        }
+                    )
+                    .is_some());
+                }
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(SafetyPolicy::STRICT)]
+    #[case(SafetyPolicy { max_step: usize::MAX, ..SafetyPolicy::STRICT })]
+    fn dampen_handles_almost_every_level_being_removable(#[case] policy: SafetyPolicy) {
+        // Every other level is far too big, with the rest going up by one.
+        let levels: Vec<usize> = (0..2000)
+            .map(|idx| if idx % 2 == 0 { idx / 2 } else { 1_000_000 })
+            .collect();
+        let policy = SafetyPolicy {
+            removable_levels: levels.len() - 1,
+            ..policy
+        };
+
+        let removed = dampen(&levels, &policy).unwrap();
+
+        if policy.max_step == SafetyPolicy::STRICT.max_step {
+            assert_eq!(removed, (1..levels.len()).step_by(2).collect::<Vec<_>>());
+        } else {
+            // Any step up is allowed, so the last big level can be kept too.
+            assert_eq!(removed.len(), levels.len() / 2 - 1);
+        }
+    }
+
+    #[rstest]
+    #[case(&[7, 6, 4, 2, 1], Verdict::Safe)]
+    #[case(&[1, 2, 7, 8, 9], Verdict::Unsafe { index: 2, reason: Reason::StepTooLarge { step: 5 } })]
+    #[case(&[9, 7, 6, 2, 1], Verdict::Unsafe { index: 3, reason: Reason::StepTooLarge { step: 4 } })]
+    #[case(&[1, 3, 2, 4, 5], Verdict::SafeAfterRemoving { indices: vec![2] })]
+    #[case(&[8, 6, 4, 4, 1], Verdict::SafeAfterRemoving { indices: vec![3] })]
+    #[case(&[1, 3, 6, 7, 9], Verdict::Safe)]
+    #[case(&[1, 3, 2, 1, 5], Verdict::Unsafe { index: 2, reason: Reason::WrongDirection })]
+    #[case(&[3, 3, 3], Verdict::Unsafe { index: 1, reason: Reason::EqualNeighbours })]
+    fn test_verdict(#[case] levels: &[usize], #[case] expected: Verdict) {
+        assert_eq!(verdict(levels, &SafetyPolicy::DAMPENED), expected);
+    }
+
+    #[test]
+    fn verdicts_explain_small_steps() {
+        let policy = SafetyPolicy {
+            min_step: 2,
+            ..SafetyPolicy::STRICT
+        };
+        let verdict = verdict(&[1, 3, 4], &policy);
+
+        assert_eq!(
+            verdict,
+            Verdict::Unsafe {
+                index: 2,
+                reason: Reason::StepTooSmall { step: 1 }
+            }
+        );
+        assert_eq!(
+            verdict.to_string(),
+            "unsafe at level 2, as it is only 1 away from the level before it"
+        );
+    }
+
+    #[test]
+    fn test_assess() {
+        let reports = vec![
+            vec![7, 6, 4, 2, 1],
+            vec![1, 2, 7, 8, 9],
+            vec![9, 7, 6, 2, 1],
+            vec![1, 3, 2, 4, 5],
+            vec![8, 6, 4, 4, 1],
+            vec![1, 3, 6, 7, 9],
+        ];
+
+        let (verdicts, summary) = assess(&reports, &SafetyPolicy::DAMPENED);
+
+        assert_eq!(verdicts.len(), reports.len());
+        assert_eq!(
+            summary,
+            Summary {
+                reports: 6,
+                safe: 2,
+                dampened: 2,
+                unsafe_reports: 2,
+                removed_levels: 2,
+            }
+        );
+        assert_eq!(summary.total_safe(), 4);
+        assert_eq!(
+            summary.to_string(),
+            "6 reports: 2 safe, 2 safe after removing 2 levels, 2 unsafe"
+        );
+    }
+}
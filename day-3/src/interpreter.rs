@@ -0,0 +1,480 @@
+//! Reading the instructions hidden in corrupted memory, and running them.
+//!
+//! Adding an instruction means adding a variant to [`Instruction`], its signature to
+//! `Instruction::SIGNATURES` and `Instruction::from_parts`, and what it does to
+//! [`Machine::execute`]. The scanning, and the reporting of near misses, stays the same.
+
+use std::fmt::Display;
+
+use aoc_common::Location;
+use nom::{
+    bytes::complete::take_while_m_n,
+    character::complete::one_of,
+    combinator::{map_res, opt, recognize},
+    sequence::pair,
+    Slice,
+};
+use nom_locate::LocatedSpan;
+
+/// The most digits an argument can have, as in `mul(123,4)`. Longer numbers are near misses, which
+/// also keeps every product well within an `i64`.
+const MAX_DIGITS: usize = 3;
+
+/// A piece of the memory, which knows where in the memory it is.
+type Span<'a> = LocatedSpan<&'a str>;
+
+/// An instruction which survived the corruption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// `mul(X,Y)`: add the product of `X` and `Y` to the total.
+    Mul(i64, i64),
+    /// `do()`: enable the instructions after it.
+    Do,
+    /// `don't()`: disable the instructions after it, until the next `do()`.
+    Dont,
+}
+
+impl Instruction {
+    /// The name and number of arguments of every instruction, written `name(X,Y,...)`.
+    ///
+    /// Names which start with another name, like `don't` and `do`, must come first.
+    const SIGNATURES: [(&'static str, usize); 3] = [("mul", 2), ("don't", 0), ("do", 0)];
+
+    /// The length in bytes of the longest instruction, with every argument signed and as long as
+    /// it can be, like `mul(-123,-456)`.
+    pub(crate) const MAX_LEN: usize = {
+        let mut longest = 0;
+        let mut idx = 0;
+        while idx < Self::SIGNATURES.len() {
+            let (name, arity) = Self::SIGNATURES[idx];
+            // The brackets, an optional sign and the digits of each argument, and the commas.
+            let len = name.len() + 2 + arity * (1 + MAX_DIGITS) + arity.saturating_sub(1);
+            if len > longest {
+                longest = len;
+            }
+            idx += 1;
+        }
+        longest
+    };
+
+    /// Build the instruction from its name and arguments, which match one of the signatures.
+    fn from_parts(name: &str, args: &[i64]) -> Self {
+        match (name, args) {
+            ("mul", [x, y]) => Instruction::Mul(*x, *y),
+            ("don't", []) => Instruction::Dont,
+            ("do", []) => Instruction::Do,
+            _ => unreachable!("{name} with {} arguments has no signature", args.len()),
+        }
+    }
+
+    /// Parse a single well-formed instruction at the start of `input`.
+    pub fn parse(input: &str) -> nom::IResult<&str, Self> {
+        match parse_instruction(Span::new(input)) {
+            Ok((rest, instruction)) => Ok((rest.fragment(), instruction)),
+            Err(failure) => {
+                let at = failure.map_or(input, |(at, _)| &input[at.location_offset()..]);
+                Err(nom::Err::Error(nom::error::Error::new(
+                    at,
+                    nom::error::ErrorKind::Tag,
+                )))
+            }
+        }
+    }
+
+    /// Whether `text` is the start of an instruction name, but not a whole one.
+    pub(crate) fn is_partial_name(text: &str) -> bool {
+        Self::SIGNATURES
+            .iter()
+            .any(|(name, _)| name.len() > text.len() && name.starts_with(text))
+    }
+
+    /// Whether `do()` and `don't()` can switch this instruction off.
+    pub fn can_be_disabled(&self) -> bool {
+        match self {
+            Instruction::Mul(..) => true,
+            Instruction::Do | Instruction::Dont => false,
+        }
+    }
+}
+
+/// Parse the instruction at the start of `input`.
+///
+/// Fails with `None` if there is no instruction name there, or with where and why the instruction
+/// after the name is malformed.
+fn parse_instruction(input: Span) -> Result<(Span, Instruction), Option<(Span, &'static str)>> {
+    let (name, arity) = Instruction::SIGNATURES
+        .into_iter()
+        .find(|(name, _)| input.fragment().starts_with(name))
+        .ok_or(None)?;
+
+    let mut rest = expect_char(input.slice(name.len()..), '(', "`(`")?;
+    let mut args = Vec::with_capacity(arity);
+    for idx in 0..arity {
+        if idx > 0 {
+            rest = expect_char(rest, ',', "`,`")?;
+        }
+        let (after, arg) = parse_argument(rest).map_err(|_| {
+            // A sign at the very end is the start of a number which was cut off.
+            match *rest.fragment() {
+                "+" | "-" => Some((rest.slice(1..), "a number")),
+                _ => Some((rest, "a number")),
+            }
+        })?;
+        args.push(arg);
+        rest = after;
+    }
+    let rest = expect_char(rest, ')', "`)`")?;
+
+    Ok((rest, Instruction::from_parts(name, &args)))
+}
+
+/// Parse an argument of an instruction: a number of up to [`MAX_DIGITS`] digits, with an optional
+/// sign.
+fn parse_argument(input: Span) -> nom::IResult<Span, i64> {
+    map_res(
+        recognize(pair(
+            opt(one_of("+-")),
+            take_while_m_n(1, MAX_DIGITS, |ch: char| ch.is_ascii_digit()),
+        )),
+        |number: Span| number.fragment().parse(),
+    )(input)
+}
+
+fn expect_char<'a>(
+    input: Span<'a>,
+    ch: char,
+    expected: &'static str,
+) -> Result<Span<'a>, Option<(Span<'a>, &'static str)>> {
+    match input.fragment().strip_prefix(ch) {
+        Some(_) => Ok(input.slice(ch.len_utf8()..)),
+        None => Err(Some((input, expected))),
+    }
+}
+
+/// An instruction along with where it was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Token {
+    pub instruction: Instruction,
+    /// The byte offset of the start of the instruction in the memory.
+    pub offset: usize,
+    /// The length of the instruction in bytes.
+    pub len: usize,
+}
+
+/// The name of an instruction, followed by something which doesn't fit it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NearMiss {
+    /// The byte offset of the start of the name in the memory.
+    pub offset: usize,
+    /// Where the name starts.
+    pub start: Location,
+    /// Where the first character which doesn't fit is, or the end of the memory.
+    pub end: Location,
+    /// The text from the start of the name up to and including the first character which doesn't
+    /// fit.
+    pub text: String,
+    /// What should have come instead.
+    pub expected: &'static str,
+    /// What came instead, or `None` at the end of the memory.
+    pub found: Option<char>,
+}
+
+impl NearMiss {
+    fn new(start: Span, at: Span, expected: &'static str) -> Self {
+        let found = at.fragment().chars().next();
+        let len = at.location_offset() - start.location_offset() + found.map_or(0, char::len_utf8);
+
+        Self {
+            offset: start.location_offset(),
+            start: locate(start),
+            end: locate(at),
+            text: start.fragment()[..len].to_string(),
+            expected,
+            found,
+        }
+    }
+}
+
+impl Display for NearMiss {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} to {}: {:?} expected {}, ",
+            self.start, self.end, self.text, self.expected
+        )?;
+        match self.found {
+            Some(found) => write!(f, "found {found:?}"),
+            None => write!(f, "found the end of the memory"),
+        }
+    }
+}
+
+fn locate(span: Span) -> Location {
+    Location::new(span.location_line() as usize, span.get_utf8_column())
+}
+
+/// Finds every well-formed instruction in corrupted memory, in order, along with the near misses.
+///
+/// Anything which isn't an instruction is skipped over, one character at a time, so instructions
+/// hiding inside the remains of broken ones, like the `mul(1,2)` in `mulmul(1,2)`, are found.
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    rest: Span<'a>,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(memory: &'a str) -> Self {
+        Self {
+            rest: Span::new(memory),
+        }
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = Result<Token, NearMiss>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Every instruction starts with a letter, so there is no need to look anywhere else.
+            let skip = self
+                .rest
+                .fragment()
+                .find(|ch: char| ch.is_ascii_alphabetic())?;
+            let candidate = self.rest.slice(skip..);
+            // Letters are a single byte, so the next character starts right after.
+            self.rest = candidate.slice(1..);
+
+            match parse_instruction(candidate) {
+                Ok((rest, instruction)) => {
+                    self.rest = rest;
+                    return Some(Ok(Token {
+                        instruction,
+                        offset: candidate.location_offset(),
+                        len: rest.location_offset() - candidate.location_offset(),
+                    }));
+                }
+                Err(Some((at, expected))) => {
+                    return Some(Err(NearMiss::new(candidate, at, expected)));
+                }
+                Err(None) => {}
+            }
+        }
+    }
+}
+
+/// Find every well-formed instruction in `memory`, in order.
+pub fn tokenize(memory: &str) -> Vec<Token> {
+    Scanner::new(memory).filter_map(Result::ok).collect()
+}
+
+/// Find every well-formed instruction in `memory`, along with every near miss, both in order.
+pub fn scan(memory: &str) -> (Vec<Token>, Vec<NearMiss>) {
+    let mut tokens = Vec::new();
+    let mut near_misses = Vec::new();
+    for scanned in Scanner::new(memory) {
+        match scanned {
+            Ok(token) => tokens.push(token),
+            Err(near_miss) => near_misses.push(near_miss),
+        }
+    }
+    (tokens, near_misses)
+}
+
+/// The state of the computer running the instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Machine {
+    /// Whether instructions are currently enabled.
+    pub enabled: bool,
+    /// Whether `do()` and `don't()` have any effect. Without them every instruction runs.
+    pub conditionals: bool,
+    /// The sum of every product so far.
+    pub total: i64,
+}
+
+impl Machine {
+    /// A machine which starts out enabled, and follows `do()` and `don't()`.
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            conditionals: true,
+            total: 0,
+        }
+    }
+
+    /// A machine which ignores `do()` and `don't()`, running every instruction.
+    pub fn without_conditionals() -> Self {
+        Self {
+            conditionals: false,
+            ..Self::new()
+        }
+    }
+
+    pub fn execute(&mut self, instruction: &Instruction) {
+        if instruction.can_be_disabled() && self.conditionals && !self.enabled {
+            return;
+        }
+
+        match instruction {
+            Instruction::Mul(x, y) => self.total += x * y,
+            Instruction::Do => self.enabled = true,
+            Instruction::Dont => self.enabled = false,
+        }
+    }
+
+    /// Run every instruction in order, returning the total afterwards.
+    pub fn run<'a>(&mut self, instructions: impl IntoIterator<Item = &'a Instruction>) -> i64 {
+        for instruction in instructions {
+            self.execute(instruction);
+        }
+        self.total
+    }
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("mul(1,2)", Instruction::Mul(1, 2), "")]
+    #[case("mul(-3,4)x", Instruction::Mul(-3, 4), "x")]
+    #[case("do()mul", Instruction::Do, "mul")]
+    #[case("don't()", Instruction::Dont, "")]
+    fn test_parse(#[case] input: &str, #[case] expected: Instruction, #[case] remainder: &str) {
+        assert_eq!(Instruction::parse(input), Ok((remainder, expected)));
+    }
+
+    #[rstest]
+    #[case("mul[1,2]")]
+    #[case("mul(1,2,3)")]
+    #[case("mul ( 2 , 4 )")]
+    #[case("mul(4*")]
+    #[case("mul(99999999999,99999999999)")]
+    #[case("do_not()")]
+    fn parse_rejects_malformed_instructions(#[case] input: &str) {
+        assert!(Instruction::parse(input).is_err());
+    }
+
+    #[test]
+    fn the_longest_instruction_is_a_mul_with_signs() {
+        assert_eq!(Instruction::MAX_LEN, "mul(-123,-456)".len());
+        assert!(Instruction::parse("mul(-123,-456)").is_ok());
+    }
+
+    #[test]
+    fn tokens_have_their_offsets() {
+        let tokens =
+            tokenize("xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))");
+
+        let found: Vec<(Instruction, usize, usize)> = tokens
+            .iter()
+            .map(|token| (token.instruction, token.offset, token.len))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Instruction::Mul(2, 4), 1, 8),
+                (Instruction::Dont, 20, 7),
+                (Instruction::Mul(5, 5), 28, 8),
+                (Instruction::Mul(11, 8), 48, 9),
+                (Instruction::Do, 59, 4),
+                (Instruction::Mul(8, 5), 64, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn instructions_inside_broken_ones_are_found() {
+        let tokens = tokenize("mulmul(1,2)mul(mul(3,4)dodo()");
+
+        let found: Vec<Instruction> = tokens.iter().map(|token| token.instruction).collect();
+        assert_eq!(
+            found,
+            [
+                Instruction::Mul(1, 2),
+                Instruction::Mul(3, 4),
+                Instruction::Do
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(Machine::new(), 48)]
+    #[case(Machine::without_conditionals(), 161)]
+    fn test_run(#[case] mut machine: Machine, #[case] expected: i64) {
+        let tokens =
+            tokenize("xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))");
+
+        assert_eq!(
+            machine.run(tokens.iter().map(|token| &token.instruction)),
+            expected
+        );
+    }
+
+    #[test]
+    fn conditionals_carry_over_between_lines() {
+        let input = r#"sdfadfmul(1,2)ølajsddo()øladsf98mul(1,2)lasd
+don't()asdfløjmul(1,2)ljøkadsfmul(1,2)ølahlsg
+øiagsdo()p4ttqpomul(1,2)povasidon't()oøahvsdmul(1,2)"#;
+        let tokens = tokenize(input);
+
+        let mut machine = Machine::new();
+        assert_eq!(
+            machine.run(tokens.iter().map(|token| &token.instruction)),
+            6
+        );
+        assert!(!machine.enabled);
+    }
+
+    #[rstest]
+    #[case("mul(4*", "mul(4*", (1, 6), "`,`", Some('*'))]
+    #[case("mul ( 2 , 4 )", "mul ", (1, 4), "`(`", Some(' '))]
+    #[case("x\n mul(1,2,3)", "mul(1,2,", (2, 9), "`)`", Some(','))]
+    #[case("mul(a,1)", "mul(a", (1, 5), "a number", Some('a'))]
+    #[case("ø mul(1,", "mul(1,", (1, 9), "a number", None)]
+    #[case("mul(3,-", "mul(3,-", (1, 8), "a number", None)]
+    #[case("mul(1234,5)", "mul(1234", (1, 8), "`,`", Some('4'))]
+    #[case("mul(99999999999,99999999999)", "mul(9999", (1, 8), "`,`", Some('9'))]
+    #[case("mul(7,-1000)", "mul(7,-1000", (1, 11), "`)`", Some('0'))]
+    fn near_misses_are_located(
+        #[case] memory: &str,
+        #[case] text: &str,
+        #[case] end: (usize, usize),
+        #[case] expected: &str,
+        #[case] found: Option<char>,
+    ) {
+        let (tokens, near_misses) = scan(memory);
+
+        assert!(tokens.is_empty());
+        assert_eq!(near_misses.len(), 1);
+        let near_miss = &near_misses[0];
+        assert_eq!(near_miss.text, text);
+        assert_eq!(near_miss.end, Location::new(end.0, end.1));
+        assert_eq!(near_miss.expected, expected);
+        assert_eq!(near_miss.found, found);
+    }
+
+    #[test]
+    fn near_misses_are_listed_alongside_instructions() {
+        let (tokens, near_misses) =
+            scan("xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))");
+
+        assert_eq!(tokens.len(), 4);
+        let found: Vec<String> = near_misses.iter().map(|m| m.to_string()).collect();
+        assert_eq!(
+            found,
+            [
+                "line 1, column 12 to line 1, column 15: \"mul[\" expected `(`, found '['",
+                "line 1, column 23 to line 1, column 25: \"do_\" expected `(`, found '_'",
+                "line 1, column 39 to line 1, column 48: \"mul(32,64]\" expected `)`, found ']'",
+            ]
+        );
+    }
+}
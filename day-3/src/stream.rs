@@ -0,0 +1,226 @@
+//! Scanning memory too large to hold at once, reading it a chunk at a time.
+
+use std::{collections::VecDeque, io::Read};
+
+use crate::interpreter::{Instruction, Machine, Scanner, Token};
+
+/// Finds every well-formed instruction in memory read from `reader`, in order.
+///
+/// The memory is read in chunks, and only the end of a chunk which could be the start of an
+/// instruction is kept until the next chunk arrives, which is never more than the longest
+/// instruction. The tokens are the same as those found by
+/// [`Scanner`], with offsets counted from the start of the stream, but near misses aren't
+/// reported.
+///
+/// Instructions are plain ASCII, so any other byte, including bytes which aren't valid UTF-8, is
+/// treated as noise.
+#[derive(Debug)]
+pub struct StreamScanner<R> {
+    reader: R,
+    chunk_size: usize,
+    /// The memory read but not yet scanned, starting at `offset` in the stream.
+    buffer: Vec<u8>,
+    offset: usize,
+    /// Tokens found in the last chunk which haven't been returned yet.
+    pending: VecDeque<Token>,
+    finished: bool,
+}
+
+impl<R: Read> StreamScanner<R> {
+    pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+    pub fn new(reader: R) -> Self {
+        Self::with_chunk_size(reader, Self::DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Read the memory `chunk_size` bytes at a time.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is 0.
+    pub fn with_chunk_size(reader: R, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "The chunks must hold at least one byte.");
+
+        Self {
+            reader,
+            chunk_size,
+            buffer: Vec::new(),
+            offset: 0,
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Read the next chunk, and scan everything which can't be changed by what comes after it.
+    fn scan_chunk(&mut self) -> std::io::Result<()> {
+        let start = self.buffer.len();
+        self.buffer.resize(start + self.chunk_size, 0);
+        let read = loop {
+            match self.reader.read(&mut self.buffer[start..]) {
+                Ok(read) => break read,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.buffer.truncate(start);
+                    return Err(err);
+                }
+            }
+        };
+        self.buffer.truncate(start + read);
+        let at_end = read == 0;
+
+        // Replacing everything but ASCII keeps the byte offsets, and never splits a character
+        // between chunks.
+        for byte in self.buffer[start..].iter_mut() {
+            if !byte.is_ascii() {
+                *byte = b'.';
+            }
+        }
+        let text = std::str::from_utf8(&self.buffer).expect("The buffer only holds ASCII.");
+
+        // Keep everything from the first instruction which might go on into the next chunk.
+        let mut keep_from = text.len();
+        for scanned in Scanner::new(text) {
+            match scanned {
+                Ok(token) => self.pending.push_back(Token {
+                    offset: self.offset + token.offset,
+                    ..token
+                }),
+                Err(near_miss) if near_miss.found.is_none() && !at_end => {
+                    keep_from = near_miss.offset;
+                    break;
+                }
+                Err(_) => {}
+            }
+        }
+        if keep_from == text.len() && !at_end {
+            // The chunk might also end in the middle of a name, like `mu` or `don'`.
+            if let Some(partial) =
+                (0..text.len()).find(|idx| Instruction::is_partial_name(&text[*idx..]))
+            {
+                keep_from = partial;
+            }
+        }
+
+        // Anything longer than the longest instruction can't turn into one, however it goes on.
+        let keep_from = keep_from.max(text.len().saturating_sub(Instruction::MAX_LEN));
+
+        self.buffer.drain(..keep_from);
+        self.offset += keep_from;
+        self.finished = at_end;
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for StreamScanner<R> {
+    type Item = std::io::Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Some(Ok(token));
+            }
+            if self.finished {
+                return None;
+            }
+            if let Err(err) = self.scan_chunk() {
+                self.finished = true;
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// Run every instruction read from `reader` on `machine`, returning the total afterwards.
+///
+/// The machine keeps its state between chunks, so a `don't()` near the end of one chunk still
+/// disables the instructions in the next.
+pub fn run_stream(reader: impl Read, machine: &mut Machine) -> std::io::Result<i64> {
+    for token in StreamScanner::new(reader) {
+        machine.execute(&token?.instruction);
+    }
+    Ok(machine.total)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::interpreter::tokenize;
+
+    const EXAMPLE: &str =
+        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[rstest]
+    #[case(EXAMPLE)]
+    #[case("mulmul(1,2)mul(mul(3,4)dodo()mul(-12,+34)mul(1234567,7654321)")]
+    #[case("ømul(1,2)ødon'tø()døo()mul(3,4)")]
+    fn finds_the_same_tokens_for_every_chunk_size(#[case] memory: &str) {
+        let expected = tokenize(memory);
+
+        for chunk_size in 1..=memory.len() + 1 {
+            let tokens: Vec<Token> = StreamScanner::with_chunk_size(memory.as_bytes(), chunk_size)
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+            assert_eq!(tokens, expected, "with chunks of {chunk_size} bytes");
+        }
+    }
+
+    #[rstest]
+    #[case(1)]
+    #[case(7)]
+    #[case(4096)]
+    fn finds_the_same_tokens_in_the_puzzle_input(#[case] chunk_size: usize) {
+        let memory = include_str!("../input2.txt");
+
+        let tokens: Vec<Token> = StreamScanner::with_chunk_size(memory.as_bytes(), chunk_size)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(tokens, tokenize(memory));
+    }
+
+    #[test]
+    fn invalid_utf8_is_noise() {
+        let memory = b"\xffmul(1,2)\xc3mul(3,4)\xe2\x82";
+
+        let tokens: Vec<(Instruction, usize)> = StreamScanner::with_chunk_size(&memory[..], 3)
+            .map(|token| token.map(|token| (token.instruction, token.offset)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            tokens,
+            [(Instruction::Mul(1, 2), 1), (Instruction::Mul(3, 4), 10)]
+        );
+    }
+
+    #[rstest]
+    #[case(b"mul(".as_slice())]
+    #[case(b"mul(1,".as_slice())]
+    #[case(b"mul(-".as_slice())]
+    #[case(b"don'".as_slice())]
+    fn holds_back_no_more_than_an_instruction(#[case] start: &[u8]) {
+        let memory = start.chain(std::io::repeat(b'7').take(10_000));
+        let mut scanner = StreamScanner::with_chunk_size(memory, 5);
+
+        while !scanner.finished {
+            scanner.scan_chunk().unwrap();
+
+            assert!(scanner.buffer.len() <= Instruction::MAX_LEN);
+        }
+        assert_eq!(scanner.pending, []);
+    }
+
+    #[rstest]
+    #[case(Machine::new(), 48)]
+    #[case(Machine::without_conditionals(), 161)]
+    fn test_run_stream(#[case] mut machine: Machine, #[case] expected: i64) {
+        assert_eq!(
+            run_stream(EXAMPLE.as_bytes(), &mut machine).unwrap(),
+            expected
+        );
+    }
+}
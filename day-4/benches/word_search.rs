@@ -0,0 +1,50 @@
+//! Compare the per-direction scanner with the single pass automaton.
+//!
+//! Run with `cargo bench -p day-4`.
+
+use aoc_grid::Grid;
+use day_4::grid::{find_pattern, Direction, MultiSearch};
+
+const INPUT: &str = include_str!("../input1.txt");
+
+const WORDS: [&[&str]; 3] = [
+    &["XMAS"],
+    &["XMAS", "MAS", "SAMX", "AXA"],
+    &[
+        "XMAS", "MAS", "SAMX", "AXA", "MMM", "SSS", "XXMA", "MASA", "AMSX", "SAXS", "XAM", "MAXS",
+        "SMAX", "AMMA", "XSAM", "MSMS",
+    ],
+];
+
+fn main() {
+    divan::main();
+}
+
+/// Look for each word in each direction separately.
+#[divan::bench(args = WORDS)]
+fn scanner(bencher: divan::Bencher, words: &[&str]) {
+    let grid = Grid::parse(INPUT).unwrap();
+
+    bencher.bench(|| {
+        words
+            .iter()
+            .flat_map(|word| {
+                Direction::ALL
+                    .into_iter()
+                    .flat_map(|direction| find_pattern(&grid, word, direction).unwrap_or_default())
+            })
+            .count()
+    });
+}
+
+/// Look for all words at once, building the automaton every time.
+#[divan::bench(args = WORDS)]
+fn automaton(bencher: divan::Bencher, words: &[&str]) {
+    let grid = Grid::parse(INPUT).unwrap();
+
+    bencher.bench(|| {
+        MultiSearch::new(words.iter().copied())
+            .find_all(&grid)
+            .len()
+    });
+}
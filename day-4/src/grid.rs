@@ -0,0 +1,640 @@
+//! Word search in grids of letters.
+//!
+//! [`find_pattern`] looks for a single word in a single direction, while [`WordSearch`] looks for
+//! any number of words and 2D [`Stencil`]s in every orientation at once. Both report each
+//! [`Match`] with the cells it covers, which [`render_matches`] can draw. For many words in large
+//! grids, [`MultiSearch`] finds them all in a single pass over the grid. [`find_fuzzy`] allows a
+//! number of wrong, missing or extra letters, for noisy grids.
+
+use aoc_grid::{Grid, IVec2};
+
+mod automaton;
+mod fuzzy;
+
+pub use aoc_grid::Direction;
+pub use automaton::MultiSearch;
+pub use fuzzy::{find_fuzzy, FuzzyMatch, Metric, Mismatch};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridError {
+    InsufficientSpace,
+}
+
+/// Count the occurrences of `pattern` in the grid, reading in `direction`.
+pub fn scan_for_pattern(
+    grid: &Grid<char>,
+    pattern: &str,
+    direction: Direction,
+) -> Result<u32, GridError> {
+    find_pattern(grid, pattern, direction).map(|matches| matches.len() as u32)
+}
+
+/// Find the occurrences of `pattern` in the grid, reading in `direction`.
+///
+/// Matches are ordered by their first letter, row by row.
+pub fn find_pattern(
+    grid: &Grid<char>,
+    pattern: &str,
+    direction: Direction,
+) -> Result<Vec<Match>, GridError> {
+    let pattern_len = pattern.chars().count();
+
+    // Verify that we have sufficient space to find the pattern in the desired direction.
+    let offset = direction.offset();
+    let enough_space = (offset.x == 0 || grid.width() >= pattern_len)
+        && (offset.y == 0 || grid.height() >= pattern_len);
+
+    if !enough_space {
+        return Err(GridError::InsufficientSpace);
+    }
+
+    let stencil = Stencil::line(pattern);
+    Ok(stencil.find(grid, 0, Orientation::reading(direction)))
+}
+
+/// Draw the grid with only the letters covered by `matches` visible, and `.` everywhere else.
+pub fn render_matches(grid: &Grid<char>, matches: &[Match]) -> String {
+    let mut covered = Grid::new(grid.width(), grid.height(), false);
+    for pos in matches.iter().flat_map(|m| m.cells.iter()) {
+        covered[*pos] = true;
+    }
+
+    grid.render_with(|pos, ch| if covered[pos] { *ch } else { '.' })
+}
+
+/// A shape of letters to look for in a grid.
+///
+/// Each cell is an offset from the anchor of the stencil, along with the letter expected there.
+/// Cells without a letter are wildcards, which match any letter as long as they are inside the
+/// grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stencil {
+    cells: Vec<(IVec2, Option<char>)>,
+}
+
+impl Stencil {
+    pub fn new(cells: impl IntoIterator<Item = (IVec2, Option<char>)>) -> Self {
+        Self {
+            cells: cells.into_iter().collect(),
+        }
+    }
+
+    /// A word written left to right, anchored at its first letter.
+    pub fn line(word: &str) -> Self {
+        Self::new(
+            word.chars()
+                .enumerate()
+                .map(|(idx, ch)| (IVec2::new(idx as i32, 0), Some(ch))),
+        )
+    }
+
+    /// Two copies of `word` crossing in an X at their middle letters, both read from the top.
+    ///
+    /// This is the "X-MAS" shape, anchored at the shared middle letter.
+    ///
+    /// # Panics
+    /// Panics if `word` doesn't have an odd number of letters, since it then has no middle letter.
+    pub fn cross(word: &str) -> Self {
+        let len = word.chars().count() as i32;
+        assert!(len % 2 == 1, "A cross needs a word with a middle letter.");
+        let mid = len / 2;
+
+        let down_right = word
+            .chars()
+            .enumerate()
+            .map(|(idx, ch)| (IVec2::new(idx as i32 - mid, idx as i32 - mid), Some(ch)));
+        let down_left = word
+            .chars()
+            .enumerate()
+            .filter(|(idx, _)| *idx as i32 != mid)
+            .map(|(idx, ch)| (IVec2::new(mid - idx as i32, idx as i32 - mid), Some(ch)));
+
+        Self::new(down_right.chain(down_left))
+    }
+
+    /// A word written downwards for its first `corner + 1` letters, then turning to the right.
+    ///
+    /// The letter at index `corner` is shared by both legs of the L.
+    pub fn l_shape(word: &str, corner: usize) -> Self {
+        Self::new(word.chars().enumerate().map(|(idx, ch)| {
+            let pos = if idx <= corner {
+                IVec2::new(0, idx as i32)
+            } else {
+                IVec2::new((idx - corner) as i32, corner as i32)
+            };
+            (pos, Some(ch))
+        }))
+    }
+
+    /// Parse a stencil from a mask, anchored at its upper left corner.
+    ///
+    /// `.` is not part of the stencil, and `?` is a wildcard. Every other character must match
+    /// exactly.
+    pub fn parse(mask: &str) -> Self {
+        Self::new(mask.lines().enumerate().flat_map(|(y, row)| {
+            row.chars()
+                .enumerate()
+                .filter(|(_, ch)| *ch != '.')
+                .map(move |(x, ch)| {
+                    let letter = (ch != '?').then_some(ch);
+                    (IVec2::new(x as i32, y as i32), letter)
+                })
+        }))
+    }
+
+    pub fn cells(&self) -> &[(IVec2, Option<char>)] {
+        &self.cells
+    }
+
+    /// Whether all the cells are in a single row, in which case the stencil can also be read along
+    /// the diagonals.
+    fn is_line(&self) -> bool {
+        self.cells.iter().all(|(offset, _)| offset.y == 0)
+    }
+
+    /// The distinct ways this stencil can be placed in a grid.
+    ///
+    /// Lines can be read in all eight directions, clockwise from `Right`. Other shapes can be
+    /// rotated in quarter turns and mirrored, since turning them by 45 degrees would stretch them.
+    /// Orientations which give the exact same shape as an earlier one, such as a palindrome read
+    /// backwards, are skipped so a placement is only reported once.
+    pub fn orientations(&self) -> Vec<Orientation> {
+        let candidates: Vec<Orientation> = if self.is_line() {
+            (0..8)
+                .map(|eighths| Orientation::reading(Direction::Right.rotate(eighths)))
+                .collect()
+        } else {
+            Direction::ORTHOGONAL
+                .into_iter()
+                .flat_map(|along| {
+                    [along.turn_right(), along.turn_left()]
+                        .map(|across| Orientation { along, across })
+                })
+                .collect()
+        };
+
+        let mut shapes = Vec::new();
+        let mut orientations = Vec::new();
+        for orientation in candidates {
+            // Compare the shapes independently of where the anchor ends up.
+            let mut shape = self.placed(orientation);
+            let corner = shape
+                .iter()
+                .fold(IVec2::MAX, |corner, (offset, _)| corner.min(*offset));
+            for (offset, _) in shape.iter_mut() {
+                *offset -= corner;
+            }
+            shape.sort_by_key(|(offset, letter)| (offset.y, offset.x, *letter));
+            if !shapes.contains(&shape) {
+                shapes.push(shape);
+                orientations.push(orientation);
+            }
+        }
+        orientations
+    }
+
+    /// Every placement of the stencil in the grid with the given orientation, row by row.
+    ///
+    /// The matches are reported as stencil number `idx`. A stencil without cells is never found,
+    /// rather than found everywhere.
+    fn find(&self, grid: &Grid<char>, idx: usize, orientation: Orientation) -> Vec<Match> {
+        let cells = self.placed(orientation);
+        if cells.is_empty() {
+            return Vec::new();
+        }
+
+        grid.positions()
+            .filter(|start| {
+                cells.iter().all(|(offset, letter)| {
+                    grid.get(*start + *offset)
+                        .is_some_and(|ch| letter.is_none_or(|letter| letter == *ch))
+                })
+            })
+            .map(|start| Match {
+                stencil: idx,
+                start,
+                orientation,
+                cells: cells.iter().map(|(offset, _)| start + *offset).collect(),
+            })
+            .collect()
+    }
+
+    /// The cells of the stencil, with their offsets turned to `orientation`.
+    fn placed(&self, orientation: Orientation) -> Vec<(IVec2, Option<char>)> {
+        self.cells
+            .iter()
+            .map(|(offset, letter)| (orientation.apply(*offset), *letter))
+            .collect()
+    }
+}
+
+/// How a stencil is laid out in the grid.
+///
+/// The columns of the stencil run `along` the first direction, and its rows run `across` the
+/// second. A word in the default orientation reads `Right`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Orientation {
+    pub along: Direction,
+    pub across: Direction,
+}
+
+impl Orientation {
+    /// The orientation of a word read in `direction`.
+    pub fn reading(direction: Direction) -> Self {
+        Self {
+            along: direction,
+            across: direction.turn_right(),
+        }
+    }
+
+    /// Turn an offset in the stencil into an offset in the grid.
+    pub fn apply(self, offset: IVec2) -> IVec2 {
+        offset.x * self.along.offset() + offset.y * self.across.offset()
+    }
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self {
+            along: Direction::Right,
+            across: Direction::Down,
+        }
+    }
+}
+
+/// A stencil found in the grid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Match {
+    /// The index of the stencil in the [`WordSearch`].
+    pub stencil: usize,
+    /// Where the anchor of the stencil was placed. For words, this is the first letter.
+    pub start: IVec2,
+    pub orientation: Orientation,
+    /// The grid cells covered by the stencil, in the order of the stencil's cells.
+    pub cells: Vec<IVec2>,
+}
+
+impl Match {
+    /// The direction the stencil is read in. For words, this is the direction from the first
+    /// letter to the last.
+    pub fn direction(&self) -> Direction {
+        self.orientation.along
+    }
+}
+
+/// Search for several stencils in every orientation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordSearch {
+    stencils: Vec<Stencil>,
+}
+
+impl WordSearch {
+    pub fn new(stencils: impl IntoIterator<Item = Stencil>) -> Self {
+        Self {
+            stencils: stencils.into_iter().collect(),
+        }
+    }
+
+    /// Search for a list of words, written in straight lines.
+    pub fn words<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        Self::new(words.into_iter().map(Stencil::line))
+    }
+
+    pub fn stencils(&self) -> &[Stencil] {
+        &self.stencils
+    }
+
+    /// Find every placement of every stencil in the grid.
+    ///
+    /// Matches are ordered by stencil, then orientation, then position row by row.
+    pub fn find_all(&self, grid: &Grid<char>) -> Vec<Match> {
+        let mut matches = Vec::new();
+
+        for (idx, stencil) in self.stencils.iter().enumerate() {
+            for orientation in stencil.orientations() {
+                matches.extend(stencil.find(grid, idx, orientation));
+            }
+        }
+
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    const EXAMPLE: &str = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    fn to_grid(rows: &[&str]) -> Grid<char> {
+        Grid::parse(&rows.join("\n")).unwrap()
+    }
+
+    #[rstest]
+    #[case(vec!["HELLO"], Ok(1))]
+    #[case(vec!["HELLO",
+                "HELLO"], Ok(2))]
+    #[case(vec!["HHELO"], Ok(0))]
+    #[case(vec!["afosdkj123HELLOadsfal"], Ok(1))]
+    #[case(vec!["a"], Err(GridError::InsufficientSpace))]
+    fn scan_for_pattern_right(#[case] input: Vec<&str>, #[case] expected: Result<u32, GridError>) {
+        let num = scan_for_pattern(&to_grid(&input), "HELLO", Direction::Right);
+
+        assert_eq!(num, expected);
+    }
+
+    #[rstest]
+    #[case(vec!["OLLEH"], Ok(1))]
+    #[case(vec!["OLLEH",
+                "OLLEH"], Ok(2))]
+    #[case(vec!["HELLO"], Ok(0))]
+    #[case(vec!["afosOLLEH23HELLOaOLLEH"], Ok(2))]
+    #[case(vec!["a"], Err(GridError::InsufficientSpace))]
+    fn scan_for_pattern_left(#[case] input: Vec<&str>, #[case] expected: Result<u32, GridError>) {
+        let num = scan_for_pattern(&to_grid(&input), "HELLO", Direction::Left);
+
+        assert_eq!(num, expected);
+    }
+
+    #[rstest]
+    #[case(vec!["a"], Err(GridError::InsufficientSpace))]
+    #[case(vec![
+            "H",
+            "E",
+            "L",
+            "L",
+            "O"], Ok(1))]
+    #[case(vec![
+            "H****",
+            "E****",
+            "L****",
+            "L****",
+            "O****"], Ok(1))]
+    #[case(vec![
+            "*",
+            "*",
+            "*",
+            "H",
+            "E",
+            "L",
+            "L",
+            "O",
+            "*",
+            "*"], Ok(1))]
+    #[case(vec![
+            "*****",
+            "*****",
+            "H***H",
+            "E***E",
+            "L***L",
+            "L*H*L",
+            "O*E*O",
+            "**L**",
+            "H*L**",
+            "E*O**",
+            "L****",
+            "L****",
+            "O****",
+            "*****",
+            "*****"], Ok(4))]
+    fn scan_for_pattern_down(#[case] input: Vec<&str>, #[case] expected: Result<u32, GridError>) {
+        let num = scan_for_pattern(&to_grid(&input), "HELLO", Direction::Down);
+
+        assert_eq!(num, expected);
+    }
+
+    #[rstest]
+    #[case(vec!["a"], Err(GridError::InsufficientSpace))]
+    #[case(vec![
+            "O",
+            "L",
+            "L",
+            "E",
+            "H",
+        ], Ok(1))]
+    #[case(vec![
+            "O****",
+            "L****",
+            "L****",
+            "E****",
+            "H****",
+        ], Ok(1))]
+    #[case(vec![
+            "*",
+            "*",
+            "O",
+            "L",
+            "L",
+            "E",
+            "H",
+            "*",
+            "*",
+            "*",
+        ], Ok(1))]
+    #[case(vec![
+            "*****",
+            "*****",
+            "O****",
+            "L****",
+            "L****",
+            "E*O**",
+            "H*L**",
+            "**L**",
+            "O*E*O",
+            "L*H*L",
+            "L***L",
+            "E***E",
+            "H***H",
+            "*****",
+            "*****",
+        ], Ok(4))]
+    fn scan_for_pattern_up(#[case] input: Vec<&str>, #[case] expected: Result<u32, GridError>) {
+        let num = scan_for_pattern(&to_grid(&input), "HELLO", Direction::Up);
+
+        assert_eq!(num, expected);
+    }
+
+    #[rstest]
+    #[case(vec!["a"], Err(GridError::InsufficientSpace))]
+    #[case(vec![
+        "****O",
+        "***L*",
+        "**L**",
+        "*E***",
+        "H****",
+    ], Ok(1))]
+    fn scan_for_pattern_up_right(
+        #[case] input: Vec<&str>,
+        #[case] expected: Result<u32, GridError>,
+    ) {
+        let num = scan_for_pattern(&to_grid(&input), "HELLO", Direction::UpRight);
+
+        assert_eq!(num, expected);
+    }
+
+    #[rstest]
+    #[case(vec!["a"], Err(GridError::InsufficientSpace))]
+    #[case(vec![
+        "O****",
+        "*L***",
+        "**L**",
+        "***E*",
+        "****H",
+    ], Ok(1))]
+    fn scan_for_pattern_up_left(
+        #[case] input: Vec<&str>,
+        #[case] expected: Result<u32, GridError>,
+    ) {
+        let num = scan_for_pattern(&to_grid(&input), "HELLO", Direction::UpLeft);
+
+        assert_eq!(num, expected);
+    }
+
+    #[rstest]
+    #[case(vec!["a"], Err(GridError::InsufficientSpace))]
+    #[case(vec![
+        "****H",
+        "***E*",
+        "**L**",
+        "*L***",
+        "O****",
+    ], Ok(1))]
+    fn scan_for_pattern_down_left(
+        #[case] input: Vec<&str>,
+        #[case] expected: Result<u32, GridError>,
+    ) {
+        let num = scan_for_pattern(&to_grid(&input), "HELLO", Direction::DownLeft);
+
+        assert_eq!(num, expected);
+    }
+
+    #[rstest]
+    #[case(vec!["a"], Err(GridError::InsufficientSpace))]
+    #[case(vec![
+        "H****",
+        "*E***",
+        "**L**",
+        "***L*",
+        "****O",
+    ], Ok(1))]
+    fn scan_for_pattern_down_right(
+        #[case] input: Vec<&str>,
+        #[case] expected: Result<u32, GridError>,
+    ) {
+        let num = scan_for_pattern(&to_grid(&input), "HELLO", Direction::DownRight);
+
+        assert_eq!(num, expected);
+    }
+
+    #[rstest]
+    #[case(Stencil::line("XMAS"), 8)]
+    #[case(Stencil::line("ABA"), 4)]
+    #[case(Stencil::cross("MAS"), 4)]
+    #[case(Stencil::l_shape("XMAS", 2), 8)]
+    #[case(Stencil::parse("A?\n?A"), 2)]
+    fn test_orientations(#[case] stencil: Stencil, #[case] expected: usize) {
+        assert_eq!(stencil.orientations().len(), expected);
+    }
+
+    #[test]
+    fn parse_skips_dots_and_keeps_wildcards() {
+        let stencil = Stencil::parse("M.S\n.?.");
+
+        assert_eq!(
+            stencil.cells(),
+            [
+                (IVec2::new(0, 0), Some('M')),
+                (IVec2::new(2, 0), Some('S')),
+                (IVec2::new(1, 1), None),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(WordSearch::words(["XMAS"]), 18)]
+    #[case(WordSearch::words(["XMAS", "SAMX"]), 36)]
+    #[case(WordSearch::new([Stencil::cross("MAS")]), 9)]
+    #[case(WordSearch::new([Stencil::parse("X?A")]), 46)]
+    fn test_find_all(#[case] search: WordSearch, #[case] expected: usize) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        assert_eq!(search.find_all(&grid).len(), expected);
+    }
+
+    #[test]
+    fn find_all_reports_stencil_position_and_orientation() {
+        let grid = to_grid(&["X...", "M...", "ASAM", "...."]);
+        let search = WordSearch::new([Stencil::l_shape("XMAS", 2), Stencil::line("MAS")]);
+
+        assert_eq!(
+            search.find_all(&grid),
+            [
+                Match {
+                    stencil: 0,
+                    start: IVec2::new(0, 0),
+                    orientation: Orientation::default(),
+                    cells: vec![
+                        IVec2::new(0, 0),
+                        IVec2::new(0, 1),
+                        IVec2::new(0, 2),
+                        IVec2::new(1, 2),
+                    ],
+                },
+                Match {
+                    stencil: 1,
+                    start: IVec2::new(3, 2),
+                    orientation: Orientation {
+                        along: Direction::Left,
+                        across: Direction::Up,
+                    },
+                    cells: vec![IVec2::new(3, 2), IVec2::new(2, 2), IVec2::new(1, 2)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn find_pattern_reports_start_direction_and_cells() {
+        let grid = to_grid(&["..S.", ".A..", "M...", "...."]);
+
+        let matches = find_pattern(&grid, "MAS", Direction::UpRight).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].start, IVec2::new(0, 2));
+        assert_eq!(matches[0].direction(), Direction::UpRight);
+        assert_eq!(
+            matches[0].cells,
+            [IVec2::new(0, 2), IVec2::new(1, 1), IVec2::new(2, 0)]
+        );
+    }
+
+    #[test]
+    fn empty_patterns_are_never_found() {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        for direction in Direction::ALL {
+            assert_eq!(find_pattern(&grid, "", direction), Ok(vec![]));
+        }
+        let search = WordSearch::new([Stencil::line(""), Stencil::parse("..\n..")]);
+        assert_eq!(search.find_all(&grid), []);
+    }
+
+    #[test]
+    fn render_matches_hides_unmatched_letters() {
+        let grid = to_grid(&["XMASX", "SAMXM", "AMXSA"]);
+
+        let matches = find_pattern(&grid, "XMAS", Direction::Right).unwrap();
+        assert_eq!(render_matches(&grid, &matches), "XMAS.\n.....\n.....");
+        assert_eq!(render_matches(&grid, &[]), ".....\n.....\n.....");
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+
+use aoc_grid::{Grid, IVec2};
+
+use super::{Direction, Match, Orientation};
+
+/// A word found by the automaton, ending at the current letter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Output {
+    /// The index of the word in the [`MultiSearch`].
+    word: usize,
+    /// The number of letters in the word.
+    len: usize,
+    /// Whether the word was found written backwards.
+    reversed: bool,
+}
+
+/// An Aho–Corasick automaton, matching every pattern in a single pass over the letters.
+///
+/// The transitions are resolved ahead of time into a table with a row per state and a column per
+/// letter in the patterns, so reading a letter is a single lookup.
+#[derive(Debug, Clone)]
+struct Automaton {
+    /// Every letter used by the patterns, sorted.
+    alphabet: Vec<char>,
+    /// The next state for each state and letter, indexed by `state * alphabet.len() + letter`.
+    transitions: Vec<usize>,
+    /// Every word ending in each state, including those ending at its suffixes.
+    outputs: Vec<Vec<Output>>,
+}
+
+impl Automaton {
+    const ROOT: usize = 0;
+
+    fn new(patterns: impl IntoIterator<Item = (Vec<char>, Output)>) -> Self {
+        let patterns: Vec<(Vec<char>, Output)> = patterns.into_iter().collect();
+
+        let mut alphabet: Vec<char> = patterns.iter().flat_map(|(p, _)| p.clone()).collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        let letters = alphabet.len();
+
+        // First build a trie of the patterns, where missing transitions are marked with `None`.
+        let mut trie: Vec<Vec<Option<usize>>> = vec![vec![None; letters]];
+        let mut outputs: Vec<Vec<Output>> = vec![vec![]];
+        for (pattern, output) in patterns {
+            let mut state = Self::ROOT;
+            for ch in pattern {
+                let letter = alphabet
+                    .binary_search(&ch)
+                    .expect("Every letter is in the alphabet.");
+                state = match trie[state][letter] {
+                    Some(next) => next,
+                    None => {
+                        trie.push(vec![None; letters]);
+                        outputs.push(vec![]);
+                        let next = trie.len() - 1;
+                        trie[state][letter] = Some(next);
+                        next
+                    }
+                };
+            }
+            outputs[state].push(output);
+        }
+
+        // Then fill in the missing transitions by following the longest suffix of each state
+        // which is also in the trie. Going breadth first makes sure the suffix, which is always
+        // shorter, is completed before the states depending on it.
+        let mut transitions = vec![Self::ROOT; trie.len() * letters];
+        let mut fail = vec![Self::ROOT; trie.len()];
+        let mut queue = VecDeque::from([Self::ROOT]);
+        while let Some(state) = queue.pop_front() {
+            for letter in 0..letters {
+                let suffix_next = transitions[fail[state] * letters + letter];
+
+                transitions[state * letters + letter] = match trie[state][letter] {
+                    Some(child) => {
+                        // The root's children have no shorter suffix than the empty one.
+                        fail[child] = if state == Self::ROOT {
+                            Self::ROOT
+                        } else {
+                            suffix_next
+                        };
+                        let inherited = outputs[fail[child]].clone();
+                        outputs[child].extend(inherited);
+                        queue.push_back(child);
+                        child
+                    }
+                    None => suffix_next,
+                };
+            }
+        }
+
+        Self {
+            alphabet,
+            transitions,
+            outputs,
+        }
+    }
+
+    /// The state after reading `ch` in `state`.
+    fn step(&self, state: usize, ch: char) -> usize {
+        match self.alphabet.binary_search(&ch) {
+            Ok(letter) => self.transitions[state * self.alphabet.len() + letter],
+            // Letters which aren't in any pattern break every partial match.
+            Err(_) => Self::ROOT,
+        }
+    }
+}
+
+/// Search for several words at once, reading every line of the grid only once.
+///
+/// Each word is added to the automaton both as written and reversed, so walking a line in one
+/// direction finds the words written in both directions along it. This visits every cell four
+/// times in total, once each for its row, column and two diagonals, regardless of how many words
+/// there are.
+#[derive(Debug, Clone)]
+pub struct MultiSearch {
+    words: Vec<String>,
+    automaton: Automaton,
+}
+
+impl MultiSearch {
+    /// The directions the lines are walked in. Their reverses are covered by the reversed words.
+    const LINES: [Direction; 4] = [
+        Direction::Right,
+        Direction::Down,
+        Direction::DownRight,
+        Direction::DownLeft,
+    ];
+
+    pub fn new<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        let words: Vec<String> = words.into_iter().map(str::to_string).collect();
+
+        let patterns = words.iter().enumerate().flat_map(|(word, text)| {
+            let forward: Vec<char> = text.chars().collect();
+            let backward: Vec<char> = forward.iter().rev().copied().collect();
+            let len = forward.len();
+
+            // Palindromes read the same both ways, so they are only reported once.
+            let reversed = (backward != forward).then(|| {
+                let output = Output {
+                    word,
+                    len,
+                    reversed: true,
+                };
+                (backward, output)
+            });
+            let output = Output {
+                word,
+                len,
+                reversed: false,
+            };
+
+            std::iter::once((forward, output)).chain(reversed)
+        });
+
+        let automaton = Automaton::new(patterns.filter(|(pattern, _)| !pattern.is_empty()));
+
+        Self { words, automaton }
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Find every occurrence of every word, in all eight directions.
+    ///
+    /// Matches use the index of the word as their stencil. They are ordered by the line they are
+    /// found on, rows first, then columns and diagonals.
+    pub fn find_all(&self, grid: &Grid<char>) -> Vec<Match> {
+        let mut matches = Vec::new();
+
+        for direction in Self::LINES {
+            // Every line starts at a cell with nothing before it.
+            let starts = grid
+                .positions()
+                .filter(|pos| !grid.contains(*pos - direction.offset()));
+
+            for start in starts {
+                self.find_in_line(grid, start, direction, &mut matches);
+            }
+        }
+
+        matches
+    }
+
+    fn find_in_line(
+        &self,
+        grid: &Grid<char>,
+        start: IVec2,
+        direction: Direction,
+        matches: &mut Vec<Match>,
+    ) {
+        let mut state = Automaton::ROOT;
+
+        for end in grid.ray(start, direction) {
+            state = self.automaton.step(state, grid[end]);
+
+            for output in self.automaton.outputs[state].iter() {
+                // A single letter reads the same in every direction, so only report it once.
+                if output.len == 1 && direction != Direction::Right {
+                    continue;
+                }
+
+                // Walk back from the last letter to find the rest of the word.
+                let mut cells: Vec<IVec2> = (0..output.len as i32)
+                    .rev()
+                    .map(|back| end - back * direction.offset())
+                    .collect();
+                let direction = if output.reversed {
+                    cells.reverse();
+                    direction.reverse()
+                } else {
+                    direction
+                };
+
+                matches.push(Match {
+                    stencil: output.word,
+                    start: cells[0],
+                    orientation: Orientation::reading(direction),
+                    cells,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::super::WordSearch;
+    use super::*;
+
+    const EXAMPLE: &str = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    fn sorted(mut matches: Vec<Match>) -> Vec<Match> {
+        matches.sort_by_key(|m| {
+            (
+                m.stencil,
+                m.cells.iter().map(|c| c.to_array()).collect::<Vec<_>>(),
+            )
+        });
+        matches
+    }
+
+    #[rstest]
+    #[case(&["XMAS"])]
+    #[case(&["XMAS", "MAS", "AS", "SAMX"])]
+    #[case(&["MSM", "A", "XMASX"])]
+    #[case(&["NOPE"])]
+    fn finds_the_same_as_word_search(#[case] words: &[&str]) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        let expected = WordSearch::words(words.iter().copied()).find_all(&grid);
+        let found = MultiSearch::new(words.iter().copied()).find_all(&grid);
+
+        assert_eq!(sorted(found), sorted(expected));
+    }
+
+    #[test]
+    fn overlapping_words_are_all_found() {
+        let grid = Grid::parse("SHERS").unwrap();
+
+        let found = MultiSearch::new(["HE", "SHE", "HERS", "REH"]).find_all(&grid);
+        let found: Vec<(usize, IVec2, Direction)> = found
+            .iter()
+            .map(|m| (m.stencil, m.start, m.direction()))
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                (1, IVec2::new(0, 0), Direction::Right),
+                (0, IVec2::new(1, 0), Direction::Right),
+                (3, IVec2::new(3, 0), Direction::Left),
+                (2, IVec2::new(1, 0), Direction::Right),
+            ]
+        );
+    }
+}
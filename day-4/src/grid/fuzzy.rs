@@ -0,0 +1,322 @@
+use aoc_grid::{Grid, IVec2};
+
+use super::{Direction, GridError, Match, Orientation};
+
+/// How the distance between a word and the letters in the grid is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// Only letters which are swapped for another count, so matches are as long as the word.
+    Hamming,
+    /// Letters may also be missing or extra, so matches can be shorter or longer than the word.
+    Levenshtein,
+}
+
+/// One difference between the word and the letters found in the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mismatch {
+    /// The grid has `found` where the word has `expected`.
+    Substituted {
+        cell: IVec2,
+        expected: char,
+        found: char,
+    },
+    /// The grid has a letter which isn't in the word.
+    Extra { cell: IVec2, found: char },
+    /// The letter at `index` in the word is missing from the grid.
+    Missing { index: usize, expected: char },
+}
+
+/// A word found in the grid with up to a tolerated number of differences.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FuzzyMatch {
+    /// Where the letters were found. These are the cells actually read, which for
+    /// [`Metric::Levenshtein`] may be fewer or more than the letters in the word.
+    pub location: Match,
+    /// The differences, in the order they appear along the word.
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl FuzzyMatch {
+    /// The distance between the word and the letters found. An exact match has distance 0.
+    pub fn distance(&self) -> usize {
+        self.mismatches.len()
+    }
+}
+
+/// Find `pattern` in the grid reading in `direction`, allowing up to `tolerance` differences.
+///
+/// At most one match is reported for each starting cell: the one with the fewest differences,
+/// preferring the length closest to the word. With [`Metric::Levenshtein`] a word may also be
+/// found starting next to an exact match, such as "MAS" for "XMAS" with the "X" missing.
+///
+/// An empty pattern has no letters to start a match from, so it is never found.
+pub fn find_fuzzy(
+    grid: &Grid<char>,
+    pattern: &str,
+    direction: Direction,
+    metric: Metric,
+    tolerance: usize,
+) -> Result<Vec<FuzzyMatch>, GridError> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    if pattern.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let (shortest, longest) = match metric {
+        Metric::Hamming => (pattern.len(), pattern.len()),
+        Metric::Levenshtein => (
+            pattern.len().saturating_sub(tolerance).max(1),
+            pattern.len() + tolerance,
+        ),
+    };
+
+    // Verify that we have sufficient space for the shortest match in the desired direction.
+    let offset = direction.offset();
+    let enough_space =
+        (offset.x == 0 || grid.width() >= shortest) && (offset.y == 0 || grid.height() >= shortest);
+
+    if !enough_space {
+        return Err(GridError::InsufficientSpace);
+    }
+
+    let matches = grid
+        .positions()
+        .filter_map(|start| {
+            let cells: Vec<IVec2> = grid.ray(start, direction).take(longest).collect();
+            if cells.len() < shortest {
+                return None;
+            }
+
+            let (len, mismatches) = match metric {
+                Metric::Hamming => hamming(grid, &pattern, &cells),
+                Metric::Levenshtein => levenshtein(grid, &pattern, &cells, tolerance)?,
+            };
+            (mismatches.len() <= tolerance).then_some((cells, len, mismatches))
+        })
+        .map(|(mut cells, len, mismatches)| {
+            // Only keep the cells which were read, leaving out any trailing ones.
+            cells.truncate(len);
+
+            FuzzyMatch {
+                location: Match {
+                    stencil: 0,
+                    start: cells[0],
+                    orientation: Orientation::reading(direction),
+                    cells,
+                },
+                mismatches,
+            }
+        })
+        .collect();
+
+    Ok(matches)
+}
+
+/// Compare the word letter by letter with the cells.
+///
+/// Returns the number of cells read along with the differences.
+fn hamming(grid: &Grid<char>, pattern: &[char], cells: &[IVec2]) -> (usize, Vec<Mismatch>) {
+    let mismatches = pattern
+        .iter()
+        .zip(cells)
+        .filter(|(expected, cell)| grid[**cell] != **expected)
+        .map(|(expected, cell)| Mismatch::Substituted {
+            cell: *cell,
+            expected: *expected,
+            found: grid[*cell],
+        })
+        .collect();
+
+    (pattern.len(), mismatches)
+}
+
+/// Find the fewest edits turning the word into the letters along some prefix of the cells.
+///
+/// Returns the length of the prefix along with the edits, or `None` if no prefix is within
+/// `tolerance` edits.
+fn levenshtein(
+    grid: &Grid<char>,
+    pattern: &[char],
+    cells: &[IVec2],
+    tolerance: usize,
+) -> Option<(usize, Vec<Mismatch>)> {
+    let text: Vec<char> = cells.iter().map(|cell| grid[*cell]).collect();
+
+    // `distances[i][j]` is the edit distance between the first `i` letters of the word and the
+    // first `j` letters of the text.
+    let mut distances = vec![vec![0; text.len() + 1]; pattern.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=pattern.len() {
+        for j in 1..=text.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(pattern[i - 1] != text[j - 1]);
+            let missing = distances[i - 1][j] + 1;
+            let extra = distances[i][j - 1] + 1;
+            distances[i][j] = substitution.min(missing).min(extra);
+        }
+    }
+
+    // Read as much of the text as gives the best match, preferring the length of the word.
+    let shortest = pattern.len().saturating_sub(tolerance).max(1);
+    let end = (shortest..=text.len()).min_by_key(|len| {
+        (
+            distances[pattern.len()][*len],
+            len.abs_diff(pattern.len()),
+            *len,
+        )
+    })?;
+    if distances[pattern.len()][end] > tolerance {
+        return None;
+    }
+
+    // Walk back through the table to find which edits were made.
+    let mut mismatches = Vec::new();
+    let (mut i, mut j) = (pattern.len(), end);
+    while i > 0 || j > 0 {
+        let current = distances[i][j];
+        if i > 0
+            && j > 0
+            && distances[i - 1][j - 1] + usize::from(pattern[i - 1] != text[j - 1]) == current
+        {
+            if pattern[i - 1] != text[j - 1] {
+                mismatches.push(Mismatch::Substituted {
+                    cell: cells[j - 1],
+                    expected: pattern[i - 1],
+                    found: text[j - 1],
+                });
+            }
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && distances[i - 1][j] + 1 == current {
+            mismatches.push(Mismatch::Missing {
+                index: i - 1,
+                expected: pattern[i - 1],
+            });
+            i -= 1;
+        } else {
+            mismatches.push(Mismatch::Extra {
+                cell: cells[j - 1],
+                found: text[j - 1],
+            });
+            j -= 1;
+        }
+    }
+    mismatches.reverse();
+
+    Some((end, mismatches))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::super::find_pattern;
+    use super::*;
+
+    const EXAMPLE: &str = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    #[rstest]
+    #[case(Metric::Hamming)]
+    #[case(Metric::Levenshtein)]
+    fn without_tolerance_only_exact_matches_are_found(#[case] metric: Metric) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        for direction in Direction::ALL {
+            let exact = find_pattern(&grid, "XMAS", direction).unwrap();
+            let fuzzy = find_fuzzy(&grid, "XMAS", direction, metric, 0).unwrap();
+
+            assert_eq!(
+                fuzzy.into_iter().map(|m| m.location).collect::<Vec<_>>(),
+                exact
+            );
+        }
+    }
+
+    #[test]
+    fn hamming_reports_substituted_letters() {
+        let grid = Grid::parse("XMAS\nXMBS\nYMBS").unwrap();
+
+        let matches = find_fuzzy(&grid, "XMAS", Direction::Right, Metric::Hamming, 1).unwrap();
+        let found: Vec<(IVec2, usize)> = matches
+            .iter()
+            .map(|m| (m.location.start, m.distance()))
+            .collect();
+
+        assert_eq!(found, [(IVec2::new(0, 0), 0), (IVec2::new(0, 1), 1)]);
+        assert_eq!(
+            matches[1].mismatches,
+            [Mismatch::Substituted {
+                cell: IVec2::new(2, 1),
+                expected: 'A',
+                found: 'B',
+            }]
+        );
+    }
+
+    #[rstest]
+    #[case("XMAS.", vec![], 4)]
+    #[case("XMS..", vec![Mismatch::Missing { index: 2, expected: 'A' }], 3)]
+    #[case("XMZAS", vec![Mismatch::Extra { cell: IVec2::new(2, 0), found: 'Z' }], 5)]
+    #[case("XMBS.", vec![Mismatch::Substituted { cell: IVec2::new(2, 0), expected: 'A', found: 'B' }], 4)]
+    fn levenshtein_reports_edits(
+        #[case] row: &str,
+        #[case] expected: Vec<Mismatch>,
+        #[case] cells: usize,
+    ) {
+        let grid = Grid::parse(row).unwrap();
+
+        let matches = find_fuzzy(&grid, "XMAS", Direction::Right, Metric::Levenshtein, 1).unwrap();
+        let at_start = matches
+            .iter()
+            .find(|m| m.location.start == IVec2::ZERO)
+            .unwrap();
+
+        assert_eq!(at_start.mismatches, expected);
+        assert_eq!(at_start.location.cells.len(), cells);
+    }
+
+    #[rstest]
+    #[case(Metric::Hamming, 0)]
+    #[case(Metric::Hamming, 2)]
+    #[case(Metric::Levenshtein, 0)]
+    #[case(Metric::Levenshtein, 2)]
+    fn empty_patterns_are_never_found(#[case] metric: Metric, #[case] tolerance: usize) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+
+        for direction in Direction::ALL {
+            assert_eq!(
+                find_fuzzy(&grid, "", direction, metric, tolerance),
+                Ok(vec![])
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(Metric::Hamming, 1, Err(GridError::InsufficientSpace))]
+    #[case(Metric::Levenshtein, 1, Ok(1))]
+    fn short_grids(
+        #[case] metric: Metric,
+        #[case] tolerance: usize,
+        #[case] expected: Result<usize, GridError>,
+    ) {
+        let grid = Grid::parse("XMS").unwrap();
+
+        assert_eq!(
+            find_fuzzy(&grid, "XMAS", Direction::Right, metric, tolerance).map(|m| m.len()),
+            expected
+        );
+    }
+}
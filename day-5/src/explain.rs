@@ -0,0 +1,379 @@
+//! Explaining why an update is out of order, and how little it takes to fix it.
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::ordering::{OrderError, RuleGraph};
+
+/// A page ordering rule `X|Y`, saying `before` must be printed before `after`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    pub before: u32,
+    pub after: u32,
+    /// The line of the input the rule was read from, starting at 1.
+    pub line: usize,
+}
+
+/// A rule which an update breaks, by printing `rule.after` before `rule.before`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Violation {
+    pub rule: Rule,
+    /// Where `rule.before` is in the update, starting at 0.
+    pub before_at: usize,
+    /// Where `rule.after` is in the update, always earlier than `before_at`.
+    pub after_at: usize,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Rule {
+            before,
+            after,
+            line,
+        } = self.rule;
+        write!(
+            f,
+            "rule {before}|{after} on line {line} is broken: {after} is at position {} but {before} is at position {}",
+            self.after_at, self.before_at
+        )
+    }
+}
+
+/// Take `page` out of the update at `from` and put it back so it ends up at `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub page: u32,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "move {} from position {} to position {}",
+            self.page, self.from, self.to
+        )
+    }
+}
+
+/// Why an update is out of order, and how to put it in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// Every rule the update breaks, ordered by where the pages are in the update.
+    pub violations: Vec<Violation>,
+    /// As few moves as possible which put the update in order, ordered by `to`.
+    ///
+    /// Every page which isn't moved stays where it is relative to the others, so the moves can be
+    /// applied by first taking out every moved page, and then putting them back in order.
+    pub moves: Vec<Move>,
+    /// The update after the moves.
+    pub repaired: Vec<u32>,
+}
+
+impl Explanation {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Every rule `update` breaks, ordered by where the pages are in the update.
+///
+/// Rules which mention pages that aren't in the update don't apply to it. Each page may only be
+/// in the update once, which the puzzle input parsers make sure of.
+pub fn violations(rules: &[Rule], update: &[u32]) -> Vec<Violation> {
+    let positions = positions(update);
+
+    let mut violations: Vec<Violation> = rules
+        .iter()
+        .filter_map(|rule| {
+            let before_at = *positions.get(&rule.before)?;
+            let after_at = *positions.get(&rule.after)?;
+            (after_at < before_at).then_some(Violation {
+                rule: *rule,
+                before_at,
+                after_at,
+            })
+        })
+        .collect();
+    violations.sort_by_key(|v| (v.after_at, v.before_at, v.rule.line));
+
+    violations
+}
+
+/// Explain why `update` is out of order, along with the fewest moves which put it in order.
+///
+/// An update which is already in order is explained by no violations and no moves. Fails if the
+/// rules between the pages of the update go in a circle, as no order satisfies them.
+///
+/// Each page may only be in the update once, as for [`violations`].
+pub fn explain(rules: &[Rule], update: &[u32]) -> Result<Explanation, OrderError> {
+    let graph = RuleGraph::new(rules.iter().map(|rule| (rule.before, rule.after)));
+    if let Some(cycle) = graph.find_cycle(update) {
+        return Err(OrderError::Cycle(cycle));
+    }
+
+    let stays = pages_to_keep(&graph, update);
+
+    // Order the pages, keeping those which stay in the order they are already in.
+    let kept: Vec<u32> = update
+        .iter()
+        .zip(stays.iter())
+        .filter(|(_, stays)| **stays)
+        .map(|(page, _)| *page)
+        .collect();
+    let repaired = RuleGraph::new(
+        rules
+            .iter()
+            .map(|rule| (rule.before, rule.after))
+            .chain(kept.windows(2).map(|pair| (pair[0], pair[1]))),
+    )
+    .order(update)
+    .expect("Keeping pages which agree with the rules can't make a circle.");
+
+    let moves = repaired
+        .iter()
+        .enumerate()
+        .filter_map(|(to, page)| {
+            let from = update.iter().position(|p| p == page)?;
+            (!stays[from]).then_some(Move {
+                page: *page,
+                from,
+                to,
+            })
+        })
+        .collect();
+
+    Ok(Explanation {
+        violations: violations(rules, update),
+        moves,
+        repaired,
+    })
+}
+
+/// The position of each page in the update.
+fn positions(update: &[u32]) -> HashMap<u32, usize> {
+    let mut positions = HashMap::new();
+    for (idx, page) in update.iter().enumerate() {
+        positions.entry(*page).or_insert(idx);
+    }
+    positions
+}
+
+/// Pick the most pages which can stay where they are, returning whether each page stays.
+///
+/// Pages can stay together as long as no rule, directly or through other pages in the update,
+/// says a later one must come before an earlier one. Being out of order like this is transitive,
+/// so the pages which can stay are the largest antichain of that order. By Dilworth's theorem it
+/// has as many pages as are left over by a largest matching from earlier to later pages, and
+/// König's theorem finds which ones.
+fn pages_to_keep(graph: &RuleGraph, update: &[u32]) -> Vec<bool> {
+    let len = update.len();
+
+    // `reaches[a][b]` is whether page `a` has to come before page `b`.
+    let mut reaches: Vec<Vec<bool>> = update
+        .iter()
+        .map(|a| update.iter().map(|b| graph.must_precede(*a, *b)).collect())
+        .collect();
+    for via in 0..len {
+        let through = reaches[via].clone();
+        for row in reaches.iter_mut().filter(|row| row[via]) {
+            for (reach, through) in row.iter_mut().zip(through.iter()) {
+                *reach |= *through;
+            }
+        }
+    }
+
+    // Page `a` is out of order with every later page `b` which has to come before it.
+    let out_of_order: Vec<Vec<usize>> = (0..len)
+        .map(|a| (a + 1..len).filter(|b| reaches[*b][a]).collect())
+        .collect();
+
+    let mut matched_with: Vec<Option<usize>> = vec![None; len];
+    for a in 0..len {
+        let mut seen = vec![false; len];
+        augment(a, &out_of_order, &mut seen, &mut matched_with);
+    }
+
+    // Walk alternating paths from the earlier pages which weren't matched. The pages reached as
+    // earlier pages but not as later ones are outside the smallest vertex cover.
+    let mut matched = vec![false; len];
+    for a in matched_with.iter().flatten() {
+        matched[*a] = true;
+    }
+    let mut reached_early: Vec<bool> = matched.iter().map(|matched| !matched).collect();
+    let mut reached_late = vec![false; len];
+    let mut stack: Vec<usize> = (0..len).filter(|a| reached_early[*a]).collect();
+    while let Some(a) = stack.pop() {
+        for b in out_of_order[a].iter() {
+            if reached_late[*b] {
+                continue;
+            }
+            reached_late[*b] = true;
+            if let Some(next) = matched_with[*b] {
+                if !reached_early[next] {
+                    reached_early[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+    }
+
+    (0..len)
+        .map(|page| reached_early[page] && !reached_late[page])
+        .collect()
+}
+
+/// Look for a path which matches `a` with one more later page, as in Kuhn's algorithm.
+fn augment(
+    a: usize,
+    out_of_order: &[Vec<usize>],
+    seen: &mut [bool],
+    matched_with: &mut [Option<usize>],
+) -> bool {
+    for b in out_of_order[a].iter() {
+        if seen[*b] {
+            continue;
+        }
+        seen[*b] = true;
+        if matched_with[*b].is_none_or(|other| augment(other, out_of_order, seen, matched_with)) {
+            matched_with[*b] = Some(a);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::ordering::Cycle;
+
+    const EXAMPLE_RULES: &str = "47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13";
+
+    fn rules(input: &str) -> Vec<Rule> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| {
+                let (before, after) = line.split_once('|').unwrap();
+                Rule {
+                    before: before.parse().unwrap(),
+                    after: after.parse().unwrap(),
+                    line: idx + 1,
+                }
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case(&[75, 47, 61, 53, 29], 0, &[75, 47, 61, 53, 29], 0)]
+    #[case(&[75, 97, 47, 61, 53], 1, &[97, 75, 47, 61, 53], 1)]
+    #[case(&[61, 13, 29], 1, &[61, 29, 13], 1)]
+    #[case(&[97, 13, 75, 29, 47], 4, &[97, 75, 47, 29, 13], 2)]
+    #[case(&[13, 29, 53, 61, 47, 75, 97], 21, &[97, 75, 47, 61, 53, 29, 13], 6)]
+    fn test_explain(
+        #[case] update: &[u32],
+        #[case] violation_count: usize,
+        #[case] repaired: &[u32],
+        #[case] move_count: usize,
+    ) {
+        let explanation = explain(&rules(EXAMPLE_RULES), update).unwrap();
+
+        assert_eq!(explanation.violations.len(), violation_count);
+        assert_eq!(explanation.is_valid(), violation_count == 0);
+        assert_eq!(explanation.repaired, repaired);
+        assert_eq!(explanation.moves.len(), move_count);
+    }
+
+    #[test]
+    fn violations_point_at_the_rule_and_pages() {
+        let violations = violations(&rules(EXAMPLE_RULES), &[75, 97, 47, 61, 53]);
+
+        assert_eq!(
+            violations,
+            [Violation {
+                rule: Rule {
+                    before: 97,
+                    after: 75,
+                    line: 16,
+                },
+                before_at: 1,
+                after_at: 0,
+            }]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "rule 97|75 on line 16 is broken: 75 is at position 0 but 97 is at position 1"
+        );
+    }
+
+    #[test]
+    fn moves_put_the_update_in_order() {
+        let update = [47, 29, 61, 13, 75];
+        let explanation = explain(&rules(EXAMPLE_RULES), &update).unwrap();
+
+        // Take out the moved pages, then put them back in.
+        let mut pages: Vec<u32> = update
+            .iter()
+            .copied()
+            .filter(|page| explanation.moves.iter().all(|m| m.page != *page))
+            .collect();
+        for m in explanation.moves.iter() {
+            assert_eq!(update[m.from], m.page);
+            pages.insert(m.to, m.page);
+        }
+
+        assert_eq!(pages, explanation.repaired);
+        assert_eq!(pages, [75, 47, 61, 29, 13]);
+        assert_eq!(
+            explanation.moves,
+            [
+                Move {
+                    page: 75,
+                    from: 4,
+                    to: 0,
+                },
+                Move {
+                    page: 29,
+                    from: 1,
+                    to: 3,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn circular_rules_cannot_be_explained() {
+        let rules = rules("47|53\n53|47");
+
+        assert_eq!(violations(&rules, &[47, 53]).len(), 1);
+        assert_eq!(
+            explain(&rules, &[47, 53]),
+            Err(OrderError::Cycle(Cycle {
+                pages: vec![47, 53]
+            }))
+        );
+    }
+}
@@ -1,2 +1,30 @@
+use aoc_common::{Solution, SolveError};
+
+pub mod explain;
+pub mod ordering;
+mod parse;
 pub mod part1;
 pub mod part2;
+pub mod rule_set;
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Answer = usize;
+
+    fn day(&self) -> u8 {
+        5
+    }
+
+    fn description(&self) -> &'static str {
+        include_str!("../daily_description.txt")
+    }
+
+    fn try_part1(&self, input: &str) -> Result<usize, SolveError> {
+        part1::try_process(input)
+    }
+
+    fn try_part2(&self, input: &str) -> Result<usize, SolveError> {
+        part2::try_process(input)
+    }
+}
@@ -0,0 +1,382 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
+
+/// The page ordering rules, as a graph from each page to the pages which must come after it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleGraph {
+    successors: HashMap<u32, HashSet<u32>>,
+}
+
+/// Pages whose rules go in a circle, each having to come before the next, and the last before the
+/// first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub pages: Vec<u32>,
+}
+
+impl Display for Cycle {
+    /// Show the cycle as `47 -> 53 -> 29 -> 47`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for page in self.pages.iter() {
+            write!(f, "{page} -> ")?;
+        }
+        match self.pages.first() {
+            Some(first) => write!(f, "{first}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// What to do when the rules between the pages of an update go in a circle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ConflictPolicy {
+    /// Refuse to order the update, reporting the shortest cycle.
+    #[default]
+    Reject,
+    /// Ignore rules until there are no cycles left. From each shortest cycle, the first rule the
+    /// update itself breaks is dropped, so the update's own order wins where the rules disagree.
+    DropConflictingRules,
+    /// When no page is free to go next, pick the one with the fewest remaining pages which must
+    /// come before it, and the earliest in the update among those.
+    TieBreak,
+}
+
+/// Why an update couldn't be put in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    /// The rules between the pages of the update go in a circle.
+    Cycle(Cycle),
+}
+
+impl RuleGraph {
+    /// Build the graph from `X|Y` rules, given as `(X, Y)`.
+    pub fn new(rules: impl IntoIterator<Item = (u32, u32)>) -> Self {
+        let mut graph = Self::default();
+        for (before, after) in rules {
+            graph.insert(before, after);
+        }
+        graph
+    }
+
+    /// Add the rule `before|after`, returning whether it is new.
+    pub fn insert(&mut self, before: u32, after: u32) -> bool {
+        self.successors.entry(before).or_default().insert(after)
+    }
+
+    /// Remove the rule `before|after`, returning whether it was there.
+    pub fn remove(&mut self, before: u32, after: u32) -> bool {
+        let Some(successors) = self.successors.get_mut(&before) else {
+            return false;
+        };
+        let removed = successors.remove(&after);
+        if successors.is_empty() {
+            self.successors.remove(&before);
+        }
+        removed
+    }
+
+    /// Whether there is a rule saying `before` must be printed before `after`.
+    pub fn must_precede(&self, before: u32, after: u32) -> bool {
+        self.successors
+            .get(&before)
+            .is_some_and(|successors| successors.contains(&after))
+    }
+
+    /// Whether the pages in the update are already in order.
+    pub fn is_ordered(&self, update: &[u32]) -> bool {
+        update.iter().enumerate().all(|(idx, page)| {
+            update[..idx]
+                .iter()
+                .all(|earlier| !self.must_precede(*page, *earlier))
+        })
+    }
+
+    /// Put the pages of an update in an order which satisfies every rule between them.
+    ///
+    /// Only the rules between pages in the update are considered. When several pages could come
+    /// next, the one appearing first in the update is picked, so updates which are already in
+    /// order are returned unchanged.
+    ///
+    /// Each rule between the pages is looked up once, so this always finishes, and fails with the
+    /// shortest cycle if the rules between the pages go in a circle.
+    pub fn order(&self, update: &[u32]) -> Result<Vec<u32>, OrderError> {
+        self.order_with(update, ConflictPolicy::Reject)
+    }
+
+    /// Like [`RuleGraph::order`], but `policy` decides what happens when the rules go in a circle.
+    pub fn order_with(
+        &self,
+        update: &[u32],
+        policy: ConflictPolicy,
+    ) -> Result<Vec<u32>, OrderError> {
+        match policy {
+            ConflictPolicy::Reject => self.sort(update, false).map_err(OrderError::Cycle),
+            ConflictPolicy::DropConflictingRules => {
+                let graph = self.without_conflicts(update);
+                Ok(graph
+                    .sort(update, false)
+                    .expect("All cycles have been broken."))
+            }
+            ConflictPolicy::TieBreak => Ok(self
+                .sort(update, true)
+                .expect("Tie-breaks always place a page.")),
+        }
+    }
+
+    /// Sort the pages topologically, always placing the earliest free page in the update next.
+    ///
+    /// The rules between the pages are looked up once, counting how many pages must come before
+    /// each one. Placing a page frees the pages after it, so the free pages are kept in a heap
+    /// by where they are in the update. If none is free, either break the tie or report the
+    /// shortest cycle.
+    fn sort(&self, update: &[u32], tie_break: bool) -> Result<Vec<u32>, Cycle> {
+        // Pages are referred to by where they are in the update.
+        let successors: Vec<Vec<usize>> = update
+            .iter()
+            .map(|before| {
+                (0..update.len())
+                    .filter(|after| self.must_precede(*before, update[*after]))
+                    .collect()
+            })
+            .collect();
+        let mut blocked_by = vec![0_usize; update.len()];
+        for after in successors.iter().flatten() {
+            blocked_by[*after] += 1;
+        }
+
+        let mut free: BinaryHeap<Reverse<usize>> = (0..update.len())
+            .filter(|idx| blocked_by[*idx] == 0)
+            .map(Reverse)
+            .collect();
+        let mut placed = vec![false; update.len()];
+        let mut ordered = Vec::with_capacity(update.len());
+
+        while ordered.len() < update.len() {
+            let next = match free.pop() {
+                Some(Reverse(idx)) => idx,
+                None if tie_break => (0..update.len())
+                    .filter(|idx| !placed[*idx])
+                    .min_by_key(|idx| (blocked_by[*idx], *idx))
+                    .expect("There are pages remaining."),
+                None => {
+                    let remaining: Vec<u32> = (0..update.len())
+                        .filter(|idx| !placed[*idx])
+                        .map(|idx| update[idx])
+                        .collect();
+                    return Err(self
+                        .find_cycle(&remaining)
+                        .expect("Pages which are all blocked must form a cycle."));
+                }
+            };
+
+            placed[next] = true;
+            ordered.push(update[next]);
+            for after in successors[next].iter() {
+                blocked_by[*after] -= 1;
+                if blocked_by[*after] == 0 && !placed[*after] {
+                    free.push(Reverse(*after));
+                }
+            }
+        }
+
+        Ok(ordered)
+    }
+
+    /// Find the shortest cycle in the rules between `pages`, if there is one.
+    ///
+    /// Among cycles of the same length, the one through the earliest page is picked, and it is
+    /// reported starting from that page.
+    pub fn find_cycle(&self, pages: &[u32]) -> Option<Cycle> {
+        let mut shortest: Option<Vec<u32>> = None;
+
+        for start in pages.iter() {
+            // Search breadth first for the shortest way back to `start`.
+            let mut came_from: HashMap<u32, u32> = HashMap::new();
+            let mut queue = VecDeque::from([*start]);
+            let mut closing = None;
+
+            while let Some(page) = queue.pop_front() {
+                if self.must_precede(page, *start) {
+                    closing = Some(page);
+                    break;
+                }
+                for next in pages.iter() {
+                    if self.must_precede(page, *next)
+                        && *next != *start
+                        && !came_from.contains_key(next)
+                    {
+                        came_from.insert(*next, page);
+                        queue.push_back(*next);
+                    }
+                }
+            }
+
+            let Some(mut page) = closing else {
+                continue;
+            };
+            let mut cycle = vec![page];
+            while page != *start {
+                page = came_from[&page];
+                cycle.push(page);
+            }
+            cycle.reverse();
+
+            if shortest.as_ref().is_none_or(|s| cycle.len() < s.len()) {
+                shortest = Some(cycle);
+            }
+        }
+
+        shortest.map(|pages| Cycle { pages })
+    }
+
+    /// A copy of the graph with rules dropped until the rules between `update`'s pages have no
+    /// cycles.
+    fn without_conflicts(&self, update: &[u32]) -> RuleGraph {
+        let mut graph = self.clone();
+        let position = |page: u32| update.iter().position(|p| *p == page);
+
+        while let Some(cycle) = graph.find_cycle(update) {
+            let rules = cycle
+                .pages
+                .iter()
+                .zip(cycle.pages.iter().cycle().skip(1))
+                .map(|(before, after)| (*before, *after));
+
+            // A circle can't go forwards through the update all the way round, so at least one
+            // rule is broken by the update.
+            let (before, after) = rules
+                .clone()
+                .find(|(before, after)| position(*after) < position(*before))
+                .or_else(|| rules.clone().next())
+                .expect("A cycle has at least one rule.");
+
+            graph.remove(before, after);
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    const EXAMPLE_RULES: [(u32, u32); 21] = [
+        (47, 53),
+        (97, 13),
+        (97, 61),
+        (97, 47),
+        (75, 29),
+        (61, 13),
+        (75, 53),
+        (29, 13),
+        (97, 29),
+        (53, 29),
+        (61, 53),
+        (97, 53),
+        (61, 29),
+        (47, 13),
+        (75, 47),
+        (97, 75),
+        (47, 61),
+        (75, 61),
+        (47, 29),
+        (75, 13),
+        (53, 13),
+    ];
+
+    #[rstest]
+    #[case(&[75, 47, 61, 53, 29], &[75, 47, 61, 53, 29])]
+    #[case(&[75, 97, 47, 61, 53], &[97, 75, 47, 61, 53])]
+    #[case(&[61, 13, 29], &[61, 29, 13])]
+    #[case(&[97, 13, 75, 29, 47], &[97, 75, 47, 29, 13])]
+    #[case(&[], &[])]
+    fn test_order(#[case] update: &[u32], #[case] expected: &[u32]) {
+        let graph = RuleGraph::new(EXAMPLE_RULES);
+
+        let ordered = graph.order(update).unwrap();
+        assert_eq!(ordered, expected);
+        assert!(graph.is_ordered(&ordered));
+        assert_eq!(graph.is_ordered(update), update == expected);
+    }
+
+    #[test]
+    fn pages_without_rules_keep_their_order() {
+        let graph = RuleGraph::new([(1, 2)]);
+
+        assert_eq!(graph.order(&[5, 2, 4, 1, 3]), Ok(vec![5, 4, 1, 2, 3]));
+    }
+
+    #[test]
+    fn long_chains_are_put_in_order() {
+        let graph = RuleGraph::new((1..500).map(|page| (page, page + 1)));
+        let update: Vec<u32> = (1..=500).rev().collect();
+
+        assert_eq!(graph.order(&update), Ok((1..=500).collect()));
+    }
+
+    #[test]
+    fn cycles_are_reported_instead_of_looping() {
+        let graph = RuleGraph::new([(47, 53), (53, 29), (29, 47), (97, 47)]);
+
+        assert_eq!(
+            graph.order(&[29, 97, 53, 47, 13]),
+            Err(OrderError::Cycle(Cycle {
+                pages: vec![29, 47, 53]
+            }))
+        );
+    }
+
+    #[rstest]
+    #[case(&[(47, 53), (53, 29), (29, 47)], &[47, 53, 29], Some("47 -> 53 -> 29 -> 47"))]
+    #[case(&[(47, 53), (53, 29), (29, 47)], &[53, 13, 47, 29], Some("53 -> 29 -> 47 -> 53"))]
+    #[case(&[(1, 2), (2, 3), (3, 4), (4, 1), (3, 1)], &[1, 2, 3, 4], Some("1 -> 2 -> 3 -> 1"))]
+    #[case(&[(1, 2), (2, 1), (2, 3), (3, 2)], &[3, 2, 1], Some("3 -> 2 -> 3"))]
+    #[case(&[(47, 53), (53, 29), (29, 47)], &[47, 53], None)]
+    #[case(&EXAMPLE_RULES, &[97, 13, 75, 29, 47], None)]
+    fn test_find_cycle(
+        #[case] rules: &[(u32, u32)],
+        #[case] pages: &[u32],
+        #[case] expected: Option<&str>,
+    ) {
+        let graph = RuleGraph::new(rules.iter().copied());
+
+        assert_eq!(
+            graph
+                .find_cycle(pages)
+                .map(|cycle| cycle.to_string())
+                .as_deref(),
+            expected
+        );
+    }
+
+    // The update has 47 before 29, so the rule `29|47` is the one dropped.
+    #[rstest]
+    #[case(ConflictPolicy::Reject, Err(OrderError::Cycle(Cycle { pages: vec![53, 29, 47] })))]
+    #[case(ConflictPolicy::DropConflictingRules, Ok(vec![47, 53, 13, 29]))]
+    #[case(ConflictPolicy::TieBreak, Ok(vec![53, 29, 47, 13]))]
+    fn test_order_with(
+        #[case] policy: ConflictPolicy,
+        #[case] expected: Result<Vec<u32>, OrderError>,
+    ) {
+        let graph = RuleGraph::new([(47, 53), (53, 29), (29, 47), (47, 13)]);
+
+        assert_eq!(graph.order_with(&[53, 13, 47, 29], policy), expected);
+    }
+
+    #[test]
+    fn dropping_conflicts_keeps_unrelated_rules() {
+        // 2 and 3 disagree, but 1 must still come last.
+        let graph = RuleGraph::new([(2, 3), (3, 2), (2, 1), (3, 1)]);
+
+        assert_eq!(
+            graph.order_with(&[1, 3, 2], ConflictPolicy::DropConflictingRules),
+            Ok(vec![3, 2, 1])
+        );
+    }
+}
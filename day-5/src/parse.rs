@@ -0,0 +1,68 @@
+//! Reading the lines of the puzzle input, shared by both parts and the streaming checker so they
+//! agree on what a rule and an update look like.
+
+use std::collections::HashSet;
+
+use aoc_common::SolveError;
+use nom::{
+    bytes::complete::tag,
+    character::complete::{self},
+    multi::separated_list1,
+    sequence::separated_pair,
+    IResult,
+};
+
+fn parse_rule(input: &str) -> IResult<&str, (u32, u32)> {
+    separated_pair(complete::u32, tag("|"), complete::u32)(input)
+}
+
+fn parse_update(input: &str) -> IResult<&str, Vec<u32>> {
+    separated_list1(tag(","), complete::u32)(input)
+}
+
+/// Parse a whole line as a rule `X|Y`, given as `(X, Y)`.
+///
+/// `line` must be a subslice of `input`, which is used to locate errors.
+pub(crate) fn parse_rule_line(input: &str, line: &str) -> Result<(u32, u32), SolveError> {
+    parse_full_line(input, line, parse_rule, "a rule on the form `X|Y`")
+}
+
+/// Parse a whole line as an update `X,Y,...`, which lists every page at most once.
+///
+/// `line` must be a subslice of `input`, which is used to locate errors.
+pub(crate) fn parse_update_line(input: &str, line: &str) -> Result<Vec<u32>, SolveError> {
+    let update = parse_full_line(input, line, parse_update, "an update on the form `X,Y,...`")?;
+
+    // The whole line is pages separated by commas, so splitting it finds where each page is.
+    let mut seen = HashSet::new();
+    for (page, text) in update.iter().zip(line.split(',')) {
+        if !seen.insert(*page) {
+            return Err(SolveError::malformed(
+                input,
+                text,
+                format!("page {page} is already in the update"),
+            ));
+        }
+    }
+
+    Ok(update)
+}
+
+/// Run `parser` on a whole line, and make sure nothing is left over.
+fn parse_full_line<'a, T>(
+    input: &str,
+    line: &'a str,
+    parser: impl Fn(&'a str) -> IResult<&'a str, T>,
+    expected: &'static str,
+) -> Result<T, SolveError> {
+    match parser(line) {
+        Ok(("", value)) => Ok(value),
+        Ok((remainder, _)) => Err(SolveError::unexpected(input, remainder, expected)),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+            Err(SolveError::unexpected(input, e.input, expected))
+        }
+        Err(nom::Err::Incomplete(_)) => {
+            Err(SolveError::unexpected(input, &line[line.len()..], expected))
+        }
+    }
+}
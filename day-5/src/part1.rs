@@ -1,59 +1,24 @@
-use std::collections::HashMap;
+use aoc_common::SolveError;
 
-use nom::{
-    bytes::complete::tag,
-    character::complete::{self},
-    multi::separated_list1,
-    sequence::separated_pair,
-    IResult,
+use crate::{
+    explain::{violations, Rule},
+    parse::{parse_rule_line, parse_update_line},
 };
 
-fn parse_rules(input: &str) -> IResult<&str, (u32, u32)> {
-    separated_pair(complete::u32, tag("|"), complete::u32)(input)
-}
-
-fn parse_updates(input: &str) -> IResult<&str, Vec<u32>> {
-    separated_list1(tag(","), complete::u32)(input)
-}
-
-#[allow(unreachable_code)]
-fn update_is_valid(num: &u32, rules: &[u32], update: &[u32]) -> bool {
-    debug_assert!(
-        update.contains(num),
-        "An update must contain the number one wants to verify rules against."
-    );
-
-    // Find self in `update`.
-    match update.iter().position(|x| x == num) {
-        Some(pos) => {
-            // Check all numbers before this index against the rules.
-            for update_member in &update[0..pos] {
-                if rules.contains(update_member) {
-                    // The rules contains this number.
-                    return false;
-                }
-            }
-        }
-        None => {
-            // The number we're checking our rules against isn't in the update list.
-            // Something is very wrong.
-            panic!("Checking rules on an update which doesn't contain the target number.");
-        }
-    }
-
-    true
+pub fn process(input: &str) -> usize {
+    try_process(input).expect("The input should be rules and updates separated by an empty line.")
 }
 
-pub fn process(input: &str) -> usize {
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
     let lines: Vec<&str> = input.lines().collect();
 
     // Loop trough the entire input.
     // Start by storing rules.
     // Upon encountering an empty line, swap to storing updates.
-    let mut rules: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut rules: Vec<Rule> = Vec::new();
     let mut updates: Vec<Vec<u32>> = Vec::new();
     let mut parsing_rules = true;
-    for line in lines {
+    for (idx, line) in lines.into_iter().enumerate() {
         if line.is_empty() {
             parsing_rules = false;
             continue;
@@ -61,27 +26,24 @@ pub fn process(input: &str) -> usize {
 
         // Grab rules.
         if parsing_rules {
-            let (_, (rule_number, trail)) = parse_rules(line).unwrap();
-            rules.entry(rule_number).or_default().push(trail);
+            let (before, after) = parse_rule_line(input, line)?;
+            rules.push(Rule {
+                before,
+                after,
+                line: idx + 1,
+            });
         } else {
             // We're done with rules, so now we grab updates
-            let (_, update) = parse_updates(line).unwrap();
+            let update = parse_update_line(input, line)?;
             updates.push(update);
         }
     }
 
-    let mut valid_updates = Vec::new();
-    // Check each update for correctness.
-    for update in updates {
-        let update_invalid = update.iter().any(|num| match rules.get(num) {
-            Some(ruleset) => !update_is_valid(num, ruleset, &update),
-            None => false,
-        });
-
-        if !update_invalid {
-            valid_updates.push(update);
-        }
-    }
+    // Only the updates which break no rules are printed.
+    let valid_updates: Vec<Vec<u32>> = updates
+        .into_iter()
+        .filter(|update| violations(&rules, update).is_empty())
+        .collect();
 
     let center_values: Vec<u32> = valid_updates
         .into_iter()
@@ -90,11 +52,14 @@ pub fn process(input: &str) -> usize {
 
     let center_value_sum: u32 = center_values.into_iter().sum();
 
-    center_value_sum as usize
+    Ok(center_value_sum as usize)
 }
 
 #[cfg(test)]
 mod tests {
+    use aoc_common::Location;
+    use rstest::rstest;
+
     use super::*;
 
     #[test]
@@ -129,4 +94,27 @@ mod tests {
 97,13,75,29,47"#;
         assert_eq!(process(input), 143);
     }
+
+    #[rstest]
+    #[case("47|53\n97x13\n\n75,47", SolveError::UnexpectedChar {
+        location: Location::new(2, 3),
+        found: 'x',
+        expected: "a rule on the form `X|Y`",
+    })]
+    #[case("47|53\n97|\n\n75,47", SolveError::UnexpectedEnd {
+        location: Location::new(2, 4),
+        expected: "a rule on the form `X|Y`",
+    })]
+    #[case("47|53\n\n75,47\n75;47", SolveError::UnexpectedChar {
+        location: Location::new(4, 3),
+        found: ';',
+        expected: "an update on the form `X,Y,...`",
+    })]
+    #[case("47|53\n\n75,47\n53,75,47,75", SolveError::Malformed {
+        location: Location::new(4, 10),
+        reason: "page 75 is already in the update".to_string(),
+    })]
+    fn try_process_reports_malformed_lines(#[case] input: &str, #[case] expected: SolveError) {
+        assert_eq!(try_process(input), Err(expected));
+    }
 }
@@ -0,0 +1,384 @@
+//! A set of rules which can change over time, checking print jobs as they arrive.
+
+use std::{fmt::Display, io::BufRead};
+
+use aoc_common::SolveError;
+
+use crate::{
+    explain::{self, Explanation, Rule, Violation},
+    ordering::{OrderError, RuleGraph},
+    parse::{parse_rule_line, parse_update_line},
+};
+
+/// The page ordering rules, kept along with where they came from.
+///
+/// Rules can be added and removed at any time, and updates are checked against the rules known at
+/// that moment.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+    graph: RuleGraph,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule, returning whether it is new.
+    ///
+    /// A rule for the same pages as an existing one is ignored, keeping the line of the first.
+    pub fn insert(&mut self, rule: Rule) -> bool {
+        let added = self.graph.insert(rule.before, rule.after);
+        if added {
+            self.rules.push(rule);
+        }
+        added
+    }
+
+    /// Remove the rule `before|after`, returning it if it was there.
+    pub fn remove(&mut self, before: u32, after: u32) -> Option<Rule> {
+        if !self.graph.remove(before, after) {
+            return None;
+        }
+        let idx = self
+            .rules
+            .iter()
+            .position(|rule| rule.before == before && rule.after == after)
+            .expect("Every rule in the graph is in the list.");
+        Some(self.rules.remove(idx))
+    }
+
+    pub fn contains(&self, before: u32, after: u32) -> bool {
+        self.graph.must_precede(before, after)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The rules, in the order they were added.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn graph(&self) -> &RuleGraph {
+        &self.graph
+    }
+
+    pub fn is_valid(&self, update: &[u32]) -> bool {
+        self.graph.is_ordered(update)
+    }
+
+    /// Every rule `update` breaks. See [`explain::violations`].
+    pub fn violations(&self, update: &[u32]) -> Vec<Violation> {
+        explain::violations(&self.rules, update)
+    }
+
+    /// Put the update in order. See [`RuleGraph::order`].
+    pub fn repair(&self, update: &[u32]) -> Result<Vec<u32>, OrderError> {
+        self.graph.order(update)
+    }
+
+    /// Explain why the update is out of order. See [`explain::explain`].
+    pub fn explain(&self, update: &[u32]) -> Result<Explanation, OrderError> {
+        explain::explain(&self.rules, update)
+    }
+
+    /// Check the print jobs read from `reader`, one line at a time.
+    pub fn check<R: BufRead>(self, reader: R) -> Checker<R> {
+        Checker {
+            reader,
+            rules: self,
+            line: 0,
+            failed: false,
+        }
+    }
+}
+
+impl Extend<Rule> for RuleSet {
+    fn extend<T: IntoIterator<Item = Rule>>(&mut self, iter: T) {
+        for rule in iter {
+            self.insert(rule);
+        }
+    }
+}
+
+impl FromIterator<Rule> for RuleSet {
+    fn from_iter<T: IntoIterator<Item = Rule>>(iter: T) -> Self {
+        let mut rules = Self::new();
+        rules.extend(iter);
+        rules
+    }
+}
+
+/// What a [`Checker`] found on a line of the stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checked {
+    /// A rule, which was added to the rule set.
+    Rule(Rule),
+    /// An update, checked against the rules read so far.
+    Update {
+        /// The line of the stream the update was read from, starting at 1.
+        line: usize,
+        pages: Vec<u32>,
+        violations: Vec<Violation>,
+        repaired: Result<Vec<u32>, OrderError>,
+    },
+}
+
+/// Why a [`Checker`] couldn't check a line.
+#[derive(Debug)]
+pub enum CheckError {
+    /// The line couldn't be read. The checker stops after this.
+    Io(std::io::Error),
+    /// The line is neither a rule nor an update. The checker carries on with the next line.
+    Invalid(SolveError),
+}
+
+impl Display for CheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckError::Io(err) => write!(f, "couldn't read the print jobs: {err}"),
+            CheckError::Invalid(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+/// Checks print jobs as they are read, for as long as the stream goes on.
+///
+/// Each line is either a rule `X|Y`, which applies to every update after it, or an update
+/// `X,Y,...`. Rules and updates may come in any order, and blank lines are skipped, so the puzzle
+/// input can be checked as well as a stream of rules and updates mixed together.
+#[derive(Debug)]
+pub struct Checker<R> {
+    reader: R,
+    rules: RuleSet,
+    /// The number of lines read so far.
+    line: usize,
+    failed: bool,
+}
+
+impl<R> Checker<R> {
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// The rules, to be changed between updates.
+    pub fn rules_mut(&mut self) -> &mut RuleSet {
+        &mut self.rules
+    }
+
+    pub fn into_rules(self) -> RuleSet {
+        self.rules
+    }
+
+    fn check_line(&mut self, line: &str) -> Result<Checked, SolveError> {
+        if line.contains('|') {
+            let (before, after) = parse_rule_line(line, line)?;
+            let rule = Rule {
+                before,
+                after,
+                line: self.line,
+            };
+            self.rules.insert(rule);
+            Ok(Checked::Rule(rule))
+        } else {
+            let pages = parse_update_line(line, line)?;
+            Ok(Checked::Update {
+                line: self.line,
+                violations: self.rules.violations(&pages),
+                repaired: self.rules.repair(&pages),
+                pages,
+            })
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Checker<R> {
+    type Item = Result<Checked, CheckError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            match self.reader.read_line(&mut buffer) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(CheckError::Io(err)));
+                }
+            }
+
+            let line = buffer.trim_end_matches(['\n', '\r']);
+            if !line.is_empty() {
+                let checked = self
+                    .check_line(line)
+                    .map_err(|err| CheckError::Invalid(err.shifted(self.line - 1)));
+                return Some(checked);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::Location;
+    use rstest::rstest;
+
+    use super::*;
+
+    const EXAMPLE: &str = "47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47";
+
+    #[test]
+    fn checks_the_puzzle_input() {
+        let checked: Vec<Checked> = RuleSet::new()
+            .check(EXAMPLE.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let (mut valid, mut repaired) = (0, 0);
+        for checked in checked {
+            let Checked::Update {
+                pages,
+                violations,
+                repaired: ordered,
+                ..
+            } = checked
+            else {
+                continue;
+            };
+            let ordered = ordered.expect("The example has no cycles.");
+            if violations.is_empty() {
+                valid += pages[pages.len() / 2];
+            } else {
+                repaired += ordered[ordered.len() / 2];
+            }
+        }
+
+        assert_eq!((valid, repaired), (143, 123));
+    }
+
+    #[test]
+    fn rules_apply_to_the_updates_after_them() {
+        let mut checker = RuleSet::new().check("53,47\n47|53\n53,47\n".as_bytes());
+
+        let Some(Ok(Checked::Update { violations, .. })) = checker.next() else {
+            panic!("The first line is an update.");
+        };
+        assert!(violations.is_empty());
+        assert!(matches!(checker.next(), Some(Ok(Checked::Rule(_)))));
+        let Some(Ok(Checked::Update {
+            line, violations, ..
+        })) = checker.next()
+        else {
+            panic!("The third line is an update.");
+        };
+        assert_eq!(line, 3);
+        assert_eq!(violations.len(), 1);
+        assert!(checker.next().is_none());
+    }
+
+    #[test]
+    fn rules_can_be_removed_between_updates() {
+        let mut checker = RuleSet::new().check("47|53\n53,47\n53,47\n".as_bytes());
+
+        checker.next();
+        assert!(matches!(
+            checker.next(),
+            Some(Ok(Checked::Update { repaired: Ok(pages), .. })) if pages == [47, 53]
+        ));
+        let removed = checker.rules_mut().remove(47, 53);
+        assert_eq!(
+            removed,
+            Some(Rule {
+                before: 47,
+                after: 53,
+                line: 1,
+            })
+        );
+        assert!(matches!(
+            checker.next(),
+            Some(Ok(Checked::Update { repaired: Ok(pages), .. })) if pages == [53, 47]
+        ));
+        assert!(checker.into_rules().is_empty());
+    }
+
+    #[rstest]
+    #[case("47|53\n\n97x13", SolveError::UnexpectedChar {
+        location: Location::new(3, 3),
+        found: 'x',
+        expected: "an update on the form `X,Y,...`",
+    })]
+    #[case("47|53\n97|", SolveError::UnexpectedEnd {
+        location: Location::new(2, 4),
+        expected: "a rule on the form `X|Y`",
+    })]
+    #[case("47|53\n\n47,53,47", SolveError::Malformed {
+        location: Location::new(3, 7),
+        reason: "page 47 is already in the update".to_string(),
+    })]
+    fn invalid_lines_are_reported_and_skipped(#[case] input: &str, #[case] expected: SolveError) {
+        let checked: Vec<Result<Checked, CheckError>> =
+            RuleSet::new().check(input.as_bytes()).collect();
+
+        assert_eq!(checked.len(), 2);
+        assert!(checked[0].is_ok());
+        assert!(matches!(&checked[1], Err(CheckError::Invalid(err)) if *err == expected));
+    }
+
+    #[test]
+    fn duplicate_rules_keep_their_first_line() {
+        let rules: RuleSet = [(47, 53, 1), (53, 29, 2), (47, 53, 3)]
+            .into_iter()
+            .map(|(before, after, line)| Rule {
+                before,
+                after,
+                line,
+            })
+            .collect();
+
+        assert_eq!(rules.len(), 2);
+        assert!(rules.contains(47, 53));
+        assert_eq!(rules.violations(&[53, 47])[0].rule.line, 1);
+        assert!(!rules.is_valid(&[29, 53]));
+    }
+}
@@ -1,190 +1,40 @@
-use std::{collections::HashSet, hash::Hash};
+use std::collections::HashSet;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Position {
-    x: usize,
-    y: usize,
-}
-
-impl From<(usize, usize)> for Position {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-impl Direction {
-    fn next(self) -> Direction {
-        match self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Step {
-    position: Position,
-    direction: Direction,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Path {
-    steps: Vec<Step>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Hinderance {
-    Obstacle,
-    OutOfBounds,
-}
-
-fn find_start_position(grid: &[&str]) -> Position {
-    let mut x = 0;
-    let mut y = 0;
-    for (i, line) in grid.iter().enumerate() {
-        if let Some(j) = line.find('^') {
-            x = j;
-            y = i;
-            break;
-        }
-    }
-    (x, y).into()
-}
-
-fn move_position(pos: Position, direction: Direction) -> Option<Position> {
-    match direction {
-        Direction::Up => {
-            let y = pos.y.checked_sub(1);
-            y.map(|y| Position { x: pos.x, y })
-        }
-        Direction::Down => Some(Position {
-            x: pos.x,
-            y: pos.y + 1,
-        }),
-        Direction::Left => {
-            let x = pos.x.checked_sub(1);
-            x.map(|x| Position { x, y: pos.y })
-        }
-        Direction::Right => Some(Position {
-            x: pos.x + 1,
-            y: pos.y,
-        }),
-    }
-}
-
-fn is_within_bounds(pos: Position, grid: &[&str]) -> bool {
-    (0..grid.len()).contains(&pos.y) && (0..grid[pos.y].len()).contains(&pos.x)
-}
-
-/// Predict the path of the guard starting from the given position and direction
-/// until it hits an obstacle or goes out of bounds.
-fn predict_guard_path(
-    start_position: Position,
-    direction: Direction,
-    grid: &[&str],
-) -> (Path, Hinderance) {
-    let mut visited_positions = Path { steps: vec![] };
-    let mut current_position = start_position;
+use aoc_common::SolveError;
+use aoc_grid::IVec2;
 
-    loop {
-        if !is_within_bounds(current_position, grid) {
-            return (visited_positions, Hinderance::OutOfBounds);
-        }
-
-        let current_char = grid[current_position.y]
-            .chars()
-            .nth(current_position.x)
-            .unwrap();
-        if current_char == '#' {
-            return (visited_positions, Hinderance::Obstacle);
-        }
-
-        visited_positions.steps.push(Step {
-            position: current_position,
-            direction,
-        });
-
-        current_position = if let Some(pos) = move_position(current_position, direction) {
-            pos
-        } else {
-            return (visited_positions, Hinderance::OutOfBounds);
-        };
-    }
-}
+use crate::patrol::{parse_map, Patrol};
 
 pub fn process(input: &str) -> usize {
-    // Find start position.
-    let grid: Vec<&str> = input.lines().collect();
-    let start_position = find_start_position(&grid);
-
-    let mut direction = Direction::Up;
-    let mut current_position = start_position;
-    let mut visited_positions = Vec::new();
-    loop {
-        let (path, hinderance) = predict_guard_path(current_position, direction, &grid);
-
-        // Check if the guard has visited the same position, in the same direction.
-        // If so we have an infinite loop.
-        if visited_positions
-            .iter()
-            .rev()
-            .take(path.steps.len())
-            .any(|step| path.steps.contains(step))
-        {
-            println!(
-                "Infinite loop detected, breaking. \n Last position: {:?}",
-                current_position
-            );
-            break; // Infinite loop detected.
-        }
-
-        // Remove the last position since it is contained in the new list.
-        visited_positions.pop();
-
-        visited_positions.extend(path.steps);
-        if hinderance == Hinderance::OutOfBounds {
-            println!(
-                "Leaving the grid at {:?}",
-                visited_positions.last().unwrap().position
-            );
-            break;
-        }
-
-        // Update the current position to the last position in the path.
-        if let Some(last_step) = visited_positions.last() {
-            current_position = last_step.position;
-        } else {
-            break; // No more steps, break the loop.
-        }
+    try_process(input).expect("The input should be a map with a single guard.")
+}
 
-        // Change direction.
-        direction = direction.next();
-    }
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
+    let grid = parse_map(input)?;
+    let patrol = Patrol::new(&grid).expect("The map has been checked for a guard.");
 
-    // Count the distinct positions visited.
-    let distinct_positions: HashSet<Position> =
-        visited_positions.iter().map(|step| step.position).collect();
+    // Stop if the guard comes back to where it has been facing the same way, as it would walk
+    // in a loop forever.
+    let mut seen = HashSet::new();
+    let visited: HashSet<IVec2> = patrol
+        .steps()
+        .take_while(|step| seen.insert(*step))
+        .map(|step| step.position)
+        .collect();
 
-    distinct_positions.len()
+    Ok(visited.len())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc_grid::Grid;
     use rstest::rstest;
 
+    fn to_grid(rows: &[&str]) -> Grid<char> {
+        Grid::parse(&rows.join("\n")).unwrap()
+    }
+
     #[test]
     fn test_process() {
         let input = r#"....#.....
@@ -206,109 +56,21 @@ mod tests {
           ".....",
           "..^..",
           "....."],
-          Position{x:2, y:2})]
+          IVec2::new(2, 2))]
     #[case(
         &[".....",
           ".....",
           ".....",
           "....^"],
-          Position{x:4, y:3})]
+          IVec2::new(4, 3))]
     #[case(
         &["^....",
           ".....",
           ".....",
           "....."],
-          Position{x:0, y:0})]
-    fn test_find_start_position(#[case] input: &[&str], #[case] expected: Position) {
-        assert_eq!(find_start_position(input), expected);
-    }
-
-    #[rstest]
-    #[case(6, Hinderance::Obstacle, Direction::Up,
-        &["....#.....",
-          ".........#",
-          "..........",
-          "..#.......",
-          ".......#..",
-          "..........",
-          ".#..^.....",
-          "........#.",
-          "#.........",
-          "......#..."])]
-    #[case(6, Hinderance::OutOfBounds, Direction::Right,
-        &["....#.....",
-          ".........#",
-          "..........",
-          "..#.......",
-          ".......#..",
-          "..........",
-          ".#..^.....",
-          "........#.",
-          "#.........",
-          "......#..."])]
-    #[case(3, Hinderance::Obstacle, Direction::Left,
-        &["....#.....",
-          ".........#",
-          "..........",
-          "..#.......",
-          ".......#..",
-          "..........",
-          ".#..^.....",
-          "........#.",
-          "#.........",
-          "......#..."])]
-    #[case(4, Hinderance::OutOfBounds, Direction::Down,
-        &["....#.....",
-          ".........#",
-          "..........",
-          "..#.......",
-          ".......#..",
-          "..........",
-          ".#..^.....",
-          "........#.",
-          "#.........",
-          "......#..."])]
-    fn test_predict_guard_path(
-        #[case] expected_steps: usize,
-        #[case] expected_hinderance: Hinderance,
-        #[case] direction: Direction,
-        #[case] grid: &[&str],
-    ) {
-        let start_position = find_start_position(grid);
-        let (path, hinderance) = predict_guard_path(start_position, direction, grid);
-        assert_eq!(path.steps.len(), expected_steps);
-        assert_eq!(hinderance, expected_hinderance);
-    }
-
-    #[rstest]
-    #[case(Position { x: 2, y: 2 }, Direction::Up, Some(Position{x:2,y:1}))]
-    #[case(Position { x: 2, y: 2 }, Direction::Down, Some(Position{x:2,y:3}))]
-    #[case(Position { x: 2, y: 2 }, Direction::Left, Some(Position{x:1,y:2}))]
-    #[case(Position { x: 2, y: 2 }, Direction::Right, Some(Position{x:3,y:2}))]
-    #[case(Position { x: 0, y: 0 }, Direction::Up, None)] // Boundary case
-    #[case(Position { x: 0, y: 0 }, Direction::Left, None)] // Boundary case
-    fn test_move_position(
-        #[case] start_position: Position,
-        #[case] direction: Direction,
-        #[case] expected_position: Option<Position>,
-    ) {
-        let new_position = move_position(start_position, direction);
-        assert_eq!(new_position, expected_position);
-    }
-
-    #[rstest]
-    #[case(Position { x: 2, y: 2 }, &[".....", ".....", "....."], true)]
-    #[case(Position { x: 4, y: 2 }, &[".....", ".....", "....."], true)]
-    #[case(Position { x: 5, y: 2 }, &[".....", ".....", "....."], false)] // Out of bounds
-    #[case(Position { x: 2, y: 3 }, &[".....", ".....", "....."], false)] // Out of bounds
-    #[case(Position { x: 0, y: 0 }, &[".....", ".....", "....."], true)]
-    #[case(Position { x: 0, y: 0 }, &[], false)] // Empty grid
-    fn test_is_within_bounds(
-        #[case] position: Position,
-        #[case] grid: &[&str],
-        #[case] expected: bool,
-    ) {
-        let result = is_within_bounds(position, grid);
-        assert_eq!(result, expected);
+          IVec2::new(0, 0))]
+    fn test_start_position(#[case] input: &[&str], #[case] expected: IVec2) {
+        let grid = to_grid(input);
+        assert_eq!(Patrol::new(&grid).unwrap().start().position, expected);
     }
 }
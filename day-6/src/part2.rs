@@ -1,283 +1,176 @@
 use std::collections::HashSet;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Position {
-    x: usize,
-    y: usize,
+use aoc_common::SolveError;
+use aoc_grid::{Direction, Grid, IVec2};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::patrol::{parse_map, Patrol, Step};
+
+/// Where a guard walking straight from each position stops, for every direction.
+///
+/// Walking around the map is then one lookup per turn instead of one per step. An extra obstacle
+/// only changes the stops in its own row, column or diagonal, so it is checked on the way instead
+/// of rebuilding the table.
+struct JumpTable {
+    /// The last free position before the next obstacle, or `None` if the guard walks off the map
+    /// first, indexed by [`slot`].
+    stops: Grid<[Option<IVec2>; 8]>,
 }
 
-impl From<(usize, usize)> for Position {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
+/// Where `direction` is in [`Direction::ALL`].
+fn slot(direction: Direction) -> usize {
+    match direction {
+        Direction::Up => 0,
+        Direction::UpRight => 1,
+        Direction::Right => 2,
+        Direction::DownRight => 3,
+        Direction::Down => 4,
+        Direction::DownLeft => 5,
+        Direction::Left => 6,
+        Direction::UpLeft => 7,
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-impl Direction {
-    fn next(self) -> Direction {
-        match self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-        }
+/// How many steps in `direction` it takes to cover `delta`, assuming it lies that way.
+fn steps_along(delta: IVec2, direction: Direction) -> i32 {
+    let offset = direction.offset();
+    if offset.x != 0 {
+        delta.x * offset.x
+    } else {
+        delta.y * offset.y
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Step {
-    position: Position,
-    direction: Direction,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Path {
-    steps: Vec<Step>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Hinderance {
-    Obstacle,
-    OutOfBounds,
-}
-
-fn find_start_position(grid: &[&str]) -> Position {
-    let mut x = 0;
-    let mut y = 0;
-    for (i, line) in grid.iter().enumerate() {
-        if let Some(j) = line.find('^') {
-            x = j;
-            y = i;
-            break;
+impl JumpTable {
+    fn new(patrol: &Patrol) -> Self {
+        let grid = patrol.grid();
+        let mut stops = Grid::new(grid.width(), grid.height(), [None; 8]);
+
+        for direction in Direction::ALL {
+            let offset = direction.offset();
+            // Fill in the positions furthest along first, so the one ahead is always done.
+            let mut positions: Vec<IVec2> = grid.positions().collect();
+            positions.sort_by_key(|pos| -pos.dot(offset));
+
+            for pos in positions {
+                let ahead = pos + offset;
+                stops[pos][slot(direction)] = if !grid.contains(ahead) {
+                    None
+                } else if patrol.is_obstacle(ahead) {
+                    Some(pos)
+                } else {
+                    stops[ahead][slot(direction)]
+                };
+            }
         }
-    }
-    (x, y).into()
-}
 
-fn move_position(pos: Position, direction: Direction) -> Option<Position> {
-    match direction {
-        Direction::Up => {
-            let y = pos.y.checked_sub(1);
-            y.map(|y| Position { x: pos.x, y })
-        }
-        Direction::Down => Some(Position {
-            x: pos.x,
-            y: pos.y + 1,
-        }),
-        Direction::Left => {
-            let x = pos.x.checked_sub(1);
-            x.map(|x| Position { x, y: pos.y })
-        }
-        Direction::Right => Some(Position {
-            x: pos.x + 1,
-            y: pos.y,
-        }),
+        Self { stops }
     }
-}
-
-fn is_within_bounds(pos: Position, grid: &[&str]) -> bool {
-    (0..grid.len()).contains(&pos.y) && (0..grid[pos.y].len()).contains(&pos.x)
-}
-
-/// Predict the path of the guard starting from the given position and direction
-/// until it hits an obstacle or goes out of bounds.
-fn predict_guard_path(
-    start_position: Position,
-    direction: Direction,
-    grid: &[&str],
-) -> (Path, Hinderance) {
-    let mut visited_positions = Path { steps: vec![] };
-    let mut current_position = start_position;
-
-    loop {
-        if !is_within_bounds(current_position, grid) {
-            return (visited_positions, Hinderance::OutOfBounds);
-        }
 
-        let current_char = grid[current_position.y]
-            .chars()
-            .nth(current_position.x)
-            .unwrap();
-        if current_char == '#' {
-            return (visited_positions, Hinderance::Obstacle);
-        }
-
-        visited_positions.steps.push(Step {
-            position: current_position,
+    /// Where the guard stops walking straight from `step`, with an extra obstacle at `obstacle`.
+    fn stop(&self, step: Step, obstacle: IVec2) -> Option<IVec2> {
+        let Step {
+            position,
             direction,
-        });
-
-        current_position = if let Some(pos) = move_position(current_position, direction) {
-            pos
-        } else {
-            return (visited_positions, Hinderance::OutOfBounds);
-        };
-    }
-}
-
-/// Walk the guard from `pos` in `direction` until an infinite loop is detected or until a maximum
-/// of iterations has been exceeded.
-fn walk_until_loop(
-    pos: Position,
-    direction: Direction,
-    grid: &[&str],
-    max_iterations: usize,
-) -> Result<Step, usize> {
-    //
-
-    let mut direction = direction;
-    let mut current_position = pos;
-    let mut visited_positions = Vec::new();
-    let mut iterations_remaining = max_iterations;
-    loop {
-        if iterations_remaining == 0 {
-            // We've been walking for too long.
-            // println!("Iteration limit exhausted.");
-            return Err(visited_positions.len());
-        }
-        let (path, hinderance) = predict_guard_path(current_position, direction, grid);
-
-        // Check if the guard has visited the same position, in the same direction.
-        // If so we have an infinite loop.
-        if visited_positions
-            .iter()
-            .rev()
-            .any(|step| path.steps.contains(step))
-        {
-            // println!(
-            //     "Infinite loop detected, breaking. \n Last position: {:?}",
-            //     current_position
-            // );
-            return Ok(*path.steps.last().expect("There should be an element."));
-        }
-
-        visited_positions.extend(path.steps);
-        if hinderance == Hinderance::OutOfBounds {
-            // println!(
-            //     "Leaving the grid at {:?}",
-            //     visited_positions.last().unwrap().position
-            // );
-            return Err(visited_positions.len());
-        }
-
-        // Update the current position to the last position in the path.
-        if let Some(last_step) = visited_positions.last() {
-            current_position = last_step.position;
+        } = step;
+        let stop = self.stops[position][slot(direction)];
+
+        let delta = obstacle - position;
+        let distance = steps_along(delta, direction);
+        let ahead = distance > 0 && delta == direction.offset() * distance;
+        let in_the_way =
+            stop.is_none_or(|stop| distance <= steps_along(stop - position, direction));
+        if ahead && in_the_way {
+            Some(obstacle - direction.offset())
         } else {
-            // No more steps.
-            return Err(visited_positions.len());
+            stop
         }
-
-        // Change direction.
-        direction = direction.next();
-        iterations_remaining -= 1;
     }
-}
-
-pub fn process(input: &str) -> usize {
-    // Find start position.
-    let grid: Vec<&str> = input.lines().collect();
-    let start_position = find_start_position(&grid);
-
-    // First walk through the map once.
-    // Then we will gradually insert obstacles throughout the path taken, then check if a loop is
-    // detected.
-
-    let mut direction = Direction::Up;
-    let mut current_position = start_position;
-    let mut visited_positions = Vec::new();
-    loop {
-        let (path, hinderance) = predict_guard_path(current_position, direction, &grid);
-
-        // Check if the guard has visited the same position, in the same direction.
-        // If so we have an infinite loop.
-        if visited_positions
-            .iter()
-            .rev()
-            .any(|step| path.steps.contains(step))
-        {
-            println!(
-                "Infinite loop detected, breaking. \n Last position: {:?}",
-                current_position
-            );
-            break; // Infinite loop detected.
-        }
-
-        visited_positions.extend(path.steps);
-        if hinderance == Hinderance::OutOfBounds {
-            println!(
-                "Leaving the grid at {:?}",
-                visited_positions.last().unwrap().position
-            );
-            break;
-        }
 
-        // Update the current position to the last position in the path.
-        if let Some(last_step) = visited_positions.last() {
-            current_position = last_step.position;
-        } else {
-            break; // No more steps, break the loop.
+    /// Whether putting an obstacle at `obstacle` makes the guard walk in a loop.
+    ///
+    /// The guard is in a loop once it turns at the same position, facing the same way, at the same
+    /// point in its turns, for the second time.
+    fn loops(&self, patrol: &Patrol, obstacle: IVec2) -> bool {
+        let period = patrol.turns().period();
+        let width = self.stops.width();
+        let states = self.stops.height() * width * Direction::ALL.len() * period;
+        let mut visited = vec![0_u64; states.div_ceil(64)];
+
+        let mut step = patrol.start();
+        let mut turned = 0;
+        loop {
+            let Some(position) = self.stop(step, obstacle) else {
+                return false;
+            };
+
+            let cell = position.y as usize * width + position.x as usize;
+            let state =
+                (cell * Direction::ALL.len() + slot(step.direction)) * period + turned % period;
+            let (word, bit) = (state / 64, 1 << (state % 64));
+            if visited[word] & bit != 0 {
+                return true;
+            }
+            visited[word] |= bit;
+
+            step = Step {
+                position,
+                direction: patrol.turns().turn(step.direction, turned),
+            };
+            turned += 1;
         }
-
-        // Change direction.
-        direction = direction.next();
     }
+}
 
-    // Now we will gradually insert obstacles for every slot the guard has walked and look for
-    // loops.
-    let mut obstacle_positions_causing_loop: Vec<Position> = Vec::new();
-    for pos in visited_positions {
-        // Skip first. No need to insert an obstacle at the start position.
-        if pos.position == start_position && pos.direction == Direction::Up {
-            continue;
-        }
-
-        // Insert an obstacle at the current position.
-        let grid_param: Vec<String> = grid
-            .iter()
-            .enumerate()
-            .map(|(i, row)| {
-                if i == pos.position.y {
-                    // This is the row where we want to insert a symbol.
-                    row.to_string()
-                        .chars()
-                        .enumerate()
-                        .map(|(i, c)| if i == pos.position.x { '#' } else { c })
-                        .collect::<String>()
-                } else {
-                    row.to_string()
-                }
-            })
-            .collect();
+/// The positions where a single extra obstacle makes the guard walk in a loop.
+///
+/// Only the positions the guard walks through can change where it goes, apart from where it
+/// starts, which is taken.
+fn loop_obstacles(patrol: &Patrol) -> Vec<IVec2> {
+    // Count the turns too, as a sequence of them can send the guard somewhere new from a step it
+    // has taken before.
+    let period = patrol.turns().period();
+    let mut seen = HashSet::new();
+    let mut turned = 0;
+    let mut previous: Option<Step> = None;
+    let candidates: HashSet<IVec2> = patrol
+        .steps()
+        .take_while(|step| {
+            // Turning on the spot is the only step which doesn't move the guard.
+            if previous.is_some_and(|previous| previous.position == step.position) {
+                turned += 1;
+            }
+            previous = Some(*step);
+            seen.insert((*step, turned % period))
+        })
+        .map(|step| step.position)
+        .filter(|position| *position != patrol.start().position)
+        .collect();
+    let candidates: Vec<IVec2> = candidates.into_iter().collect();
+
+    let table = JumpTable::new(patrol);
+    candidates
+        .par_iter()
+        .filter(|obstacle| table.loops(patrol, **obstacle))
+        .copied()
+        .collect()
+}
 
-        // Rebuild the grid.
-        let grid_param = grid_param
-            .iter()
-            .map(|line| line.as_str())
-            .collect::<Vec<&str>>();
+pub fn process(input: &str) -> usize {
+    try_process(input).expect("The input should be a map with a single guard.")
+}
 
-        let loop_res = walk_until_loop(start_position, Direction::Up, &grid_param, 10_000);
-        if loop_res.is_ok() && !obstacle_positions_causing_loop.contains(&pos.position) {
-            //dbg!("Infinite loop detected with this grid:\n{:#?}", grid_param);
-            obstacle_positions_causing_loop.push(pos.position);
-        }
-    }
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
+    let grid = parse_map(input)?;
+    let patrol = Patrol::new(&grid).expect("The map has been checked for a guard.");
 
-    obstacle_positions_causing_loop.len()
+    Ok(loop_obstacles(&patrol).len())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::patrol::{Turn, TurnPolicy};
     use rstest::rstest;
 
     #[test]
@@ -295,171 +188,101 @@ mod tests {
         assert_eq!(process(input), 6);
     }
 
-    #[rstest]
-    #[case(
-        &[".....",
-          ".....",
-          "..^..",
-          "....."],
-          Position{x:2, y:2})]
-    #[case(
-        &[".....",
-          ".....",
-          ".....",
-          "....^"],
-          Position{x:4, y:3})]
-    #[case(
-        &["^....",
-          ".....",
-          ".....",
-          "....."],
-          Position{x:0, y:0})]
-    fn test_find_start_position(#[case] input: &[&str], #[case] expected: Position) {
-        assert_eq!(find_start_position(input), expected);
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn the_example_has_six_places_for_an_obstacle() {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+        let patrol = Patrol::new(&grid).unwrap();
+
+        let mut found: Vec<(i32, i32)> = loop_obstacles(&patrol)
+            .into_iter()
+            .map(|pos| (pos.x, pos.y))
+            .collect();
+        found.sort();
+
+        assert_eq!(found, [(1, 8), (3, 6), (3, 8), (6, 7), (7, 7), (7, 9)]);
     }
 
-    #[rstest]
-    #[case(6, Hinderance::Obstacle, Direction::Up,
-        &["....#.....",
-          ".........#",
-          "..........",
-          "..#.......",
-          ".......#..",
-          "..........",
-          ".#..^.....",
-          "........#.",
-          "#.........",
-          "......#..."])]
-    #[case(6, Hinderance::OutOfBounds, Direction::Right,
-        &["....#.....",
-          ".........#",
-          "..........",
-          "..#.......",
-          ".......#..",
-          "..........",
-          ".#..^.....",
-          "........#.",
-          "#.........",
-          "......#..."])]
-    #[case(3, Hinderance::Obstacle, Direction::Left,
-        &["....#.....",
-          ".........#",
-          "..........",
-          "..#.......",
-          ".......#..",
-          "..........",
-          ".#..^.....",
-          "........#.",
-          "#.........",
-          "......#..."])]
-    #[case(4, Hinderance::OutOfBounds, Direction::Down,
-        &["....#.....",
-          ".........#",
-          "..........",
-          "..#.......",
-          ".......#..",
-          "..........",
-          ".#..^.....",
-          "........#.",
-          "#.........",
-          "......#..."])]
-    fn test_predict_guard_path(
-        #[case] expected_steps: usize,
-        #[case] expected_hinderance: Hinderance,
-        #[case] direction: Direction,
-        #[case] grid: &[&str],
-    ) {
-        let start_position = find_start_position(grid);
-        let (path, hinderance) = predict_guard_path(start_position, direction, grid);
-        assert_eq!(path.steps.len(), expected_steps);
-        assert_eq!(hinderance, expected_hinderance);
+    /// Whether the guard walks in a loop on `grid`, found by walking every step.
+    fn walks_in_a_loop(grid: &Grid<char>, turns: &TurnPolicy) -> bool {
+        let walk = Patrol::new(grid).unwrap().with_turns(turns.clone());
+        // Count the turns too, as a sequence of them can send the guard somewhere new from a step
+        // it has taken before.
+        let mut seen = HashSet::new();
+        let mut turned = 0;
+        let mut previous: Option<Step> = None;
+        walk.steps().any(|step| {
+            if previous.is_some_and(|previous| previous.position == step.position) {
+                turned += 1;
+            }
+            previous = Some(step);
+            !seen.insert((step, turned % turns.period()))
+        })
     }
 
-    #[rstest]
-    #[case(Position { x: 2, y: 2 }, Direction::Up, Some(Position{x:2,y:1}))]
-    #[case(Position { x: 2, y: 2 }, Direction::Down, Some(Position{x:2,y:3}))]
-    #[case(Position { x: 2, y: 2 }, Direction::Left, Some(Position{x:1,y:2}))]
-    #[case(Position { x: 2, y: 2 }, Direction::Right, Some(Position{x:3,y:2}))]
-    #[case(Position { x: 0, y: 0 }, Direction::Up, None)] // Boundary case
-    #[case(Position { x: 0, y: 0 }, Direction::Left, None)] // Boundary case
-    fn test_move_position(
-        #[case] start_position: Position,
-        #[case] direction: Direction,
-        #[case] expected_position: Option<Position>,
-    ) {
-        let new_position = move_position(start_position, direction);
-        assert_eq!(new_position, expected_position);
+    /// Every free position where an extra obstacle makes the guard walk in a loop.
+    fn blocked_loops(grid: &Grid<char>, turns: &TurnPolicy) -> Vec<IVec2> {
+        grid.positions()
+            .filter(|pos| grid[*pos] == '.')
+            .filter(|obstacle| {
+                let mut blocked = grid.clone();
+                blocked[*obstacle] = '#';
+                walks_in_a_loop(&blocked, turns)
+            })
+            .collect()
     }
 
     #[rstest]
-    #[case(Position { x: 2, y: 2 }, &[".....", ".....", "....."], true)]
-    #[case(Position { x: 4, y: 2 }, &[".....", ".....", "....."], true)]
-    #[case(Position { x: 5, y: 2 }, &[".....", ".....", "....."], false)] // Out of bounds
-    #[case(Position { x: 2, y: 3 }, &[".....", ".....", "....."], false)] // Out of bounds
-    #[case(Position { x: 0, y: 0 }, &[".....", ".....", "....."], true)]
-    #[case(Position { x: 0, y: 0 }, &[], false)] // Empty grid
-    fn test_is_within_bounds(
-        #[case] position: Position,
-        #[case] grid: &[&str],
-        #[case] expected: bool,
-    ) {
-        let result = is_within_bounds(position, grid);
-        assert_eq!(result, expected);
+    #[case(TurnPolicy::Right)]
+    #[case(TurnPolicy::Left)]
+    #[case(TurnPolicy::Reverse)]
+    #[case(TurnPolicy::Sequence(vec![Turn::Right, Turn::Right, Turn::Left]))]
+    fn agrees_with_walking_every_step(#[case] turns: TurnPolicy) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+        let patrol = Patrol::new(&grid).unwrap().with_turns(turns.clone());
+        let table = JumpTable::new(&patrol);
+
+        for obstacle in grid.positions().filter(|pos| grid[*pos] == '.') {
+            let mut blocked = grid.clone();
+            blocked[obstacle] = '#';
+
+            assert_eq!(
+                table.loops(&patrol, obstacle),
+                walks_in_a_loop(&blocked, &turns),
+                "{obstacle}"
+            );
+        }
     }
 
-    /*
-        test-grid
-    r#"....#.....
-    .........#
-    ..........
-    ..#.......
-    .......#..
-    ..........
-    .#..^.....
-    ........#.
-    #.........
-    ......#..."#;
-        */
     #[rstest]
+    #[case(EXAMPLE, TurnPolicy::Sequence(vec![Turn::Right, Turn::Right, Turn::Left]))]
+    #[case(EXAMPLE, TurnPolicy::Sequence(vec![Turn::Left, Turn::Reverse]))]
+    // Boxed in at the start, the guard faces down a second time after its third turn, and only
+    // walks on to (2, 2) and (2, 3) after its fifth.
     #[case(
-        Position { x: 4, y: 6 },
-        Direction::Up,
-        Position { x: 3, y: 6 },
-        &["....#.....",
-          ".........#",
-          "..........",
-          "..#.......",
-          ".......#..",
-          "..........",
-          ".#..^.....",
-          "........#.",
-          "#.........",
-          "......#..."])]
-    fn test_added_obstacle_causes_loop(
-        #[case] guard_position: Position,
-        #[case] guard_direction: Direction,
-        #[case] obstacle_position: Position,
-        #[case] grid: &[&str],
+        "....\n.###\n#^.#\n.#..",
+        TurnPolicy::Sequence(vec![Turn::Reverse, Turn::Right, Turn::Left])
+    )]
+    fn loop_obstacles_agree_with_trying_every_free_position(
+        #[case] input: &str,
+        #[case] turns: TurnPolicy,
     ) {
-        // Add an obstacle to the grid.
-        let mut grid = grid
-            .iter()
-            .map(|line| line.to_string())
-            .collect::<Vec<String>>();
-        grid[obstacle_position.y] = grid[obstacle_position.y]
-            .chars()
-            .enumerate()
-            .map(|(i, c)| if i == obstacle_position.x { '#' } else { c })
-            .collect::<String>();
-
-        // Collect to the original format to conform with the function signature.
-        let grid = grid.iter().map(|line| line.as_str()).collect::<Vec<&str>>();
-
-        let inf_res = walk_until_loop(guard_position, guard_direction, &grid, 100);
-        assert!(
-            inf_res.is_ok(),
-            "We should have encountered an infinite loop, but the result was {:?}",
-            inf_res
-        );
+        let grid = Grid::parse(input).unwrap();
+        let patrol = Patrol::new(&grid).unwrap().with_turns(turns.clone());
+
+        let mut found = loop_obstacles(&patrol);
+        found.sort_by_key(|pos| (pos.y, pos.x));
+
+        assert_eq!(found, blocked_loops(&grid, &turns));
     }
 }
@@ -0,0 +1,390 @@
+//! Simulating a guard walking around a map, turning whenever something is in the way.
+//!
+//! The puzzle's guard starts at `^`, treats `#` as an obstacle and turns right, but a [`Patrol`]
+//! can start anywhere, stop at any characters and turn by any [`TurnPolicy`].
+
+use aoc_common::{Location, SolveError};
+use aoc_grid::{Direction, Grid, IVec2};
+
+/// Where the guard is and which way it is facing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Step {
+    pub position: IVec2,
+    pub direction: Direction,
+}
+
+/// A single turn the guard can make when something is in the way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Right,
+    Left,
+    Reverse,
+}
+
+impl Turn {
+    pub fn apply(self, direction: Direction) -> Direction {
+        match self {
+            Turn::Right => direction.turn_right(),
+            Turn::Left => direction.turn_left(),
+            Turn::Reverse => direction.reverse(),
+        }
+    }
+}
+
+/// How the guard turns each time something is in the way.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum TurnPolicy {
+    /// Always turn right, as in the puzzle.
+    #[default]
+    Right,
+    /// Always turn left.
+    Left,
+    /// Always turn around.
+    Reverse,
+    /// Make the turns in order, starting over after the last one.
+    ///
+    /// An empty sequence never turns, leaving the guard stuck facing the first obstacle.
+    Sequence(Vec<Turn>),
+}
+
+impl TurnPolicy {
+    /// The direction to face after turning for the `count`th time, counting from 0.
+    pub fn turn(&self, direction: Direction, count: usize) -> Direction {
+        match self {
+            TurnPolicy::Right => Turn::Right.apply(direction),
+            TurnPolicy::Left => Turn::Left.apply(direction),
+            TurnPolicy::Reverse => Turn::Reverse.apply(direction),
+            TurnPolicy::Sequence(turns) if turns.is_empty() => direction,
+            TurnPolicy::Sequence(turns) => turns[count % turns.len()].apply(direction),
+        }
+    }
+
+    /// How many turns go by before the policy starts over, so that the `count`th turn is the
+    /// same as the `count % period`th.
+    pub fn period(&self) -> usize {
+        match self {
+            TurnPolicy::Sequence(turns) => turns.len().max(1),
+            TurnPolicy::Right | TurnPolicy::Left | TurnPolicy::Reverse => 1,
+        }
+    }
+}
+
+/// The direction a guard drawn as `ch` is facing, if `ch` is one of `^ > v <`.
+pub fn guard_direction(ch: char) -> Option<Direction> {
+    match ch {
+        '^' => Some(Direction::Up),
+        '>' => Some(Direction::Right),
+        'v' => Some(Direction::Down),
+        '<' => Some(Direction::Left),
+        _ => None,
+    }
+}
+
+/// Parse the map, making sure it only contains floor, obstacles, and a single guard facing any
+/// of the ways [`guard_direction`] knows.
+pub fn parse_map(input: &str) -> Result<Grid<char>, SolveError> {
+    let grid = Grid::parse_with(input, "one of '.', '#', '^', '>', 'v', or '<'", |ch| {
+        (matches!(ch, '.' | '#') || guard_direction(ch).is_some()).then_some(ch)
+    })?;
+
+    let guards: Vec<IVec2> = grid
+        .iter()
+        .filter(|(_, ch)| guard_direction(**ch).is_some())
+        .map(|(pos, _)| pos)
+        .collect();
+
+    match guards[..] {
+        [] => Err(SolveError::NoSolution {
+            reason: "the map has no guard".to_string(),
+        }),
+        [_, second, ..] => Err(SolveError::Malformed {
+            location: Location::new(second.y as usize + 1, second.x as usize + 1),
+            reason: "the map has more than one guard".to_string(),
+        }),
+        [_] => Ok(grid),
+    }
+}
+
+/// A guard walking around a map.
+#[derive(Debug, Clone)]
+pub struct Patrol<'a> {
+    grid: &'a Grid<char>,
+    start: Step,
+    turns: TurnPolicy,
+    obstacles: Vec<char>,
+}
+
+impl<'a> Patrol<'a> {
+    /// The guard drawn on the map as one of `^ > v <`, stopping at `#` and turning right.
+    ///
+    /// Returns `None` if there is no guard on the map. If there are several, the first one in
+    /// reading order is used.
+    pub fn new(grid: &'a Grid<char>) -> Option<Self> {
+        let (position, direction) = grid
+            .iter()
+            .find_map(|(position, ch)| Some((position, guard_direction(*ch)?)))?;
+
+        Some(Self::starting_at(
+            grid,
+            Step {
+                position,
+                direction,
+            },
+        ))
+    }
+
+    /// A guard starting from `start`, stopping at `#` and turning right.
+    pub fn starting_at(grid: &'a Grid<char>, start: Step) -> Self {
+        Self {
+            grid,
+            start,
+            turns: TurnPolicy::default(),
+            obstacles: vec!['#'],
+        }
+    }
+
+    /// Turn by `turns` instead.
+    pub fn with_turns(mut self, turns: TurnPolicy) -> Self {
+        self.turns = turns;
+        self
+    }
+
+    /// Stop at `obstacles` instead of `#`.
+    pub fn with_obstacles(mut self, obstacles: impl IntoIterator<Item = char>) -> Self {
+        self.obstacles = obstacles.into_iter().collect();
+        self
+    }
+
+    pub fn grid(&self) -> &'a Grid<char> {
+        self.grid
+    }
+
+    pub fn start(&self) -> Step {
+        self.start
+    }
+
+    pub fn turns(&self) -> &TurnPolicy {
+        &self.turns
+    }
+
+    /// Whether the guard has to turn instead of walking into `position`.
+    pub fn is_obstacle(&self, position: IVec2) -> bool {
+        self.grid
+            .get(position)
+            .is_some_and(|ch| self.obstacles.contains(ch))
+    }
+
+    /// Every step of the guard, from the start until it walks off the map.
+    ///
+    /// Turning on the spot counts as a step, so the same position comes up once for every
+    /// direction the guard faces there. A guard walking in a loop never leaves, so the steps go
+    /// on forever.
+    pub fn steps(&self) -> Steps<'_, 'a> {
+        Steps {
+            patrol: self,
+            next: Some(self.start),
+            turned: 0,
+        }
+    }
+}
+
+/// The steps of a [`Patrol`], from [`Patrol::steps`].
+#[derive(Debug, Clone)]
+pub struct Steps<'p, 'a> {
+    patrol: &'p Patrol<'a>,
+    next: Option<Step>,
+    /// How many times the guard has turned so far.
+    turned: usize,
+}
+
+impl Iterator for Steps<'_, '_> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        let step = self.next?;
+        let ahead = step.position + step.direction.offset();
+
+        self.next = if !self.patrol.grid.contains(ahead) {
+            None
+        } else if self.patrol.is_obstacle(ahead) {
+            let direction = self.patrol.turns.turn(step.direction, self.turned);
+            self.turned += 1;
+            Some(Step { direction, ..step })
+        } else {
+            Some(Step {
+                position: ahead,
+                ..step
+            })
+        };
+
+        Some(step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rstest::rstest;
+
+    use super::*;
+
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    fn to_grid(rows: &[&str]) -> Grid<char> {
+        Grid::parse(&rows.join("\n")).unwrap()
+    }
+
+    fn positions(patrol: &Patrol) -> Vec<(i32, i32)> {
+        patrol
+            .steps()
+            .map(|step| (step.position.x, step.position.y))
+            .collect()
+    }
+
+    #[test]
+    fn the_example_guard_visits_41_positions() {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+        let patrol = Patrol::new(&grid).unwrap();
+        let visited: HashSet<IVec2> = patrol.steps().map(|step| step.position).collect();
+
+        assert_eq!(visited.len(), 41);
+    }
+
+    #[rstest]
+    #[case("....\n.^..\n..#.\n", Ok(()))]
+    #[case("#...\n...<\n", Ok(()))]
+    #[case(">.\n.#\n", Ok(()))]
+    #[case("..\nv.\n", Ok(()))]
+    #[case("", Err(SolveError::EmptyInput))]
+    #[case("....\n.^x.\n", Err(SolveError::UnexpectedChar {
+        location: Location::new(2, 3),
+        found: 'x',
+        expected: "one of '.', '#', '^', '>', 'v', or '<'",
+    }))]
+    #[case("..^.\n.^..\n", Err(SolveError::Malformed {
+        location: Location::new(2, 2),
+        reason: "the map has more than one guard".to_string(),
+    }))]
+    #[case("..>.\n...v\n", Err(SolveError::Malformed {
+        location: Location::new(2, 4),
+        reason: "the map has more than one guard".to_string(),
+    }))]
+    #[case("....\n....\n", Err(SolveError::NoSolution {
+        reason: "the map has no guard".to_string(),
+    }))]
+    fn test_parse_map(#[case] input: &str, #[case] expected: Result<(), SolveError>) {
+        assert_eq!(parse_map(input).map(|_| ()), expected);
+    }
+
+    #[rstest]
+    #[case(&["..#", "...", "..^"], &[(2, 2), (2, 1), (2, 1)])]
+    #[case(&["...", "#>.", "..."], &[(1, 1), (2, 1)])]
+    #[case(&["...", ".v.", ".#."], &[(1, 1), (1, 1), (0, 1)])]
+    #[case(&["<..", "...", "..."], &[(0, 0)])]
+    fn guards_can_start_facing_any_way(#[case] rows: &[&str], #[case] expected: &[(i32, i32)]) {
+        let grid = to_grid(rows);
+        let patrol = Patrol::new(&grid).unwrap();
+
+        assert_eq!(positions(&patrol), expected);
+    }
+
+    #[rstest]
+    #[case(TurnPolicy::Right, Direction::Right)]
+    #[case(TurnPolicy::Left, Direction::Left)]
+    #[case(TurnPolicy::Reverse, Direction::Down)]
+    #[case(TurnPolicy::Sequence(vec![Turn::Left, Turn::Reverse]), Direction::Left)]
+    fn guards_turn_by_the_policy(#[case] turns: TurnPolicy, #[case] expected: Direction) {
+        let grid = to_grid(&["...", ".#.", "...", ".^."]);
+        let patrol = Patrol::new(&grid).unwrap().with_turns(turns);
+
+        let turned = patrol.steps().nth(2).unwrap();
+
+        assert_eq!(
+            turned,
+            Step {
+                position: IVec2::new(1, 2),
+                direction: expected
+            }
+        );
+    }
+
+    #[test]
+    fn sequences_of_turns_start_over() {
+        let grid = to_grid(&["#.#", "...", "#.#"]);
+        let start = Step {
+            position: IVec2::new(1, 1),
+            direction: Direction::Up,
+        };
+        let turns = TurnPolicy::Sequence(vec![Turn::Right, Turn::Reverse]);
+        let patrol = Patrol::starting_at(&grid, start)
+            .with_turns(turns)
+            .with_obstacles(['#', '.']);
+
+        let directions: Vec<Direction> =
+            patrol.steps().take(5).map(|step| step.direction).collect();
+
+        assert_eq!(
+            directions,
+            [
+                Direction::Up,
+                Direction::Right,
+                Direction::Left,
+                Direction::Up,
+                Direction::Down
+            ]
+        );
+    }
+
+    #[test]
+    fn any_characters_can_be_obstacles() {
+        let grid = to_grid(&["..", "x.", "^."]);
+
+        assert_eq!(
+            positions(&Patrol::new(&grid).unwrap()),
+            [(0, 2), (0, 1), (0, 0)]
+        );
+        assert_eq!(
+            positions(&Patrol::new(&grid).unwrap().with_obstacles(['x', '#'])),
+            [(0, 2), (0, 2), (1, 2)]
+        );
+    }
+
+    #[rstest]
+    #[case(Direction::Up, 6, true)]
+    #[case(Direction::Right, 6, false)]
+    #[case(Direction::Left, 3, true)]
+    #[case(Direction::Down, 4, false)]
+    fn guards_walk_straight_until_something_is_in_the_way(
+        #[case] direction: Direction,
+        #[case] straight: usize,
+        #[case] blocked: bool,
+    ) {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+        let start = Step {
+            position: IVec2::new(4, 6),
+            direction,
+        };
+        let patrol = Patrol::starting_at(&grid, start);
+
+        let steps: Vec<Step> = patrol.steps().take(straight + 1).collect();
+
+        assert!(steps[..straight]
+            .iter()
+            .all(|step| step.direction == direction));
+        // Being blocked turns the guard on the spot, where walking off the map ends the patrol.
+        assert_eq!(
+            steps.get(straight).map(|step| step.position),
+            blocked.then_some(steps[straight - 1].position)
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! The calibration equations, and reading them from the puzzle input.
+
+use std::fmt::Display;
+
+use aoc_common::SolveError;
+use nom::{
+    bytes::complete::{tag, take_until},
+    combinator::all_consuming,
+    multi::separated_list1,
+    IResult,
+};
+
+use crate::{number::Number, operator::Operator};
+
+/// A calibration equation, missing the operators between its numbers.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Equation<N = u64> {
+    /// The number on the left of the colon, which the numbers should give.
+    pub result: N,
+    /// The numbers on the right of the colon, in order.
+    pub numbers: Vec<N>,
+}
+
+impl<N: Number> Equation<N> {
+    /// Write out the equation with `operators` between the numbers, like `81 + 40 * 27`.
+    ///
+    /// # Panics
+    ///
+    /// If there isn't exactly one operator between each pair of numbers.
+    pub fn render(&self, operators: &[&dyn Operator<N>]) -> String {
+        assert_eq!(
+            operators.len() + 1,
+            self.numbers.len(),
+            "Every pair of numbers needs an operator between them."
+        );
+
+        let mut expression = self.numbers[0].to_string();
+        for (operator, number) in operators.iter().zip(&self.numbers[1..]) {
+            expression.push_str(&format!(" {} {number}", operator.symbol()));
+        }
+        expression
+    }
+}
+
+impl<N: Display> Display for Equation<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.result)?;
+        for number in self.numbers.iter() {
+            write!(f, " {number}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse every equation in the input, skipping blank lines.
+///
+/// A number too big for an `N` is an error, so pick `u128` for inputs which don't fit in `u64`.
+pub fn parse_equations<N: Number>(input: &str) -> Result<Vec<Equation<N>>, SolveError> {
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| parse_full_line(input, line))
+        .collect()
+}
+
+fn parse_line<N: Number>(input: &str) -> IResult<&str, Equation<N>> {
+    // Grab the first bit of the input, the target result.
+    let (input, result) = take_until(": ")(input)?;
+    let (_, result) = all_consuming(N::parse)(result)?;
+
+    // Eat the leading ": ", then start extracting numbers.
+    let (input, _) = tag(": ")(input)?;
+
+    // Extract the numbers.
+    let (input, numbers) = separated_list1(tag(" "), N::parse)(input)?;
+
+    Ok((input, Equation { result, numbers }))
+}
+
+/// Parse a whole line as an equation, and make sure nothing is left over.
+///
+/// `line` must be a subslice of `input`, which is used to locate errors.
+pub(crate) fn parse_full_line<N: Number>(
+    input: &str,
+    line: &str,
+) -> Result<Equation<N>, SolveError> {
+    const EXPECTED: &str = "an equation on the form `result: numbers`";
+
+    match parse_line(line) {
+        Ok(("", equation)) => Ok(equation),
+        Ok((remainder, _)) => Err(SolveError::unexpected(input, remainder, EXPECTED)),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+            Err(SolveError::unexpected(input, e.input, EXPECTED))
+        }
+        Err(nom::Err::Incomplete(_)) => {
+            Err(SolveError::unexpected(input, &line[line.len()..], EXPECTED))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::operator::Builtin;
+
+    #[rstest]
+    #[case("190: 10 19", Equation{result: 190, numbers: vec![10, 19]})]
+    #[case("3267: 81 40 27", Equation{result: 3267, numbers: vec![81, 40, 27]})]
+    #[case("83: 17 5", Equation{result: 83, numbers: vec![17, 5]})]
+    #[case("156: 15 6", Equation{result: 156, numbers: vec![15, 6]})]
+    #[case("7290: 6 8 6 15", Equation{result: 7290, numbers: vec![6, 8, 6, 15]})]
+    #[case("161011: 16 10 13", Equation{result: 161011, numbers: vec![16, 10, 13]})]
+    #[case("192: 17 8 14", Equation{result: 192, numbers: vec![17, 8, 14]})]
+    #[case("21037: 9 7 18 13", Equation{result: 21037, numbers: vec![9, 7, 18, 13]})]
+    #[case("292: 11 6 16 20", Equation{result: 292, numbers: vec![11, 6, 16, 20]})]
+    fn test_parse_equations(#[case] input: &str, #[case] expected: Equation) {
+        let (_input, result) = parse_line(input).unwrap();
+
+        assert_eq!(result, expected);
+        assert_eq!(result.to_string(), input);
+    }
+
+    #[test]
+    fn numbers_too_big_for_u64_need_u128() {
+        let input = "36893488147419103230: 18446744073709551615 2";
+
+        assert!(parse_equations::<u64>(input).is_err());
+        assert_eq!(
+            parse_equations::<u128>(input).unwrap(),
+            [Equation {
+                result: 2 * u64::MAX as u128,
+                numbers: vec![u64::MAX as u128, 2],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let equation: Equation = Equation {
+            result: 7290,
+            numbers: vec![6, 8, 6, 15],
+        };
+
+        assert_eq!(
+            equation.render(&[
+                &Builtin::Multiply,
+                &Builtin::CONCATENATE,
+                &Builtin::Multiply
+            ]),
+            "6 * 8 || 6 * 15"
+        );
+    }
+}
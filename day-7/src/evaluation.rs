@@ -0,0 +1,462 @@
+//! The order the operators of an equation are worked out in.
+//!
+//! Left to right, the puzzle's own rule, is solved backwards from the result by
+//! [`crate::solver`]. Once some operators bind tighter than others the last operator written is
+//! no longer the last one applied, so the other orders are solved forwards instead, remembering
+//! where the search has already been.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use crate::{number::Number, operator::Operator};
+
+/// The order the operators of an equation are worked out in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Evaluation {
+    /// Strictly left to right, ignoring precedence, as in the puzzle.
+    #[default]
+    LeftToRight,
+    /// Operators which bind tighter go first, like `*` before `+`, and left to right otherwise.
+    ///
+    /// See [`Operator::precedence`].
+    Precedence,
+    /// Any parentheses may be added, so an equation is true if some way of grouping the numbers
+    /// gives the result.
+    ///
+    /// Every grouping is tried for every choice of operators, so this is only practical for short
+    /// equations.
+    AnyParentheses,
+}
+
+/// A name which isn't one of the [`Evaluation`] orders.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnknownEvaluation {
+    pub name: String,
+}
+
+impl Display for UnknownEvaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown evaluation order {:?}, expected `left-to-right`, `precedence` or `parentheses`",
+            self.name
+        )
+    }
+}
+
+impl std::error::Error for UnknownEvaluation {}
+
+impl FromStr for Evaluation {
+    type Err = UnknownEvaluation;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left-to-right" => Ok(Evaluation::LeftToRight),
+            "precedence" => Ok(Evaluation::Precedence),
+            "parentheses" => Ok(Evaluation::AnyParentheses),
+            _ => Err(UnknownEvaluation {
+                name: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// The biggest value which can still lead to `result`, if values bigger than it can never come
+/// back down.
+///
+/// That is when every operator [keeps growing](Operator::keeps_growing), and no number is 0.
+/// Forward searches drop bigger values straight away, instead of carrying them along until they
+/// overflow.
+fn ceiling<N: Number>(result: N, numbers: &[N], operators: &[&dyn Operator<N>]) -> Option<N> {
+    let growing = operators.iter().all(|operator| operator.keeps_growing());
+    (growing && !numbers.contains(&N::ZERO)).then_some(result)
+}
+
+fn fits<N: Number>(ceiling: Option<N>, value: N) -> bool {
+    ceiling.is_none_or(|ceiling| value <= ceiling)
+}
+
+/// Part of an equation worked out with [`Evaluation::Precedence`], up to some number.
+///
+/// Operators waiting for tighter ones after them are kept with their left-hand side, so the
+/// precedence goes up along `pending`. There is at most one entry per level of precedence, which
+/// keeps the number of states small enough to remember.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Partial<N> {
+    pending: Vec<(N, usize)>,
+    value: N,
+}
+
+/// A search forwards through the numbers of an equation, with [`Evaluation::Precedence`].
+///
+/// Operators are referred to by where they are in the set.
+pub(crate) struct PrecedenceSearch<'a, N> {
+    result: N,
+    numbers: &'a [N],
+    operators: &'a [&'a dyn Operator<N>],
+    /// The biggest value worth following, from [`ceiling`].
+    ceiling: Option<N>,
+    /// The partial results from which the rest of the numbers can't give the result, along with
+    /// how many numbers they took.
+    dead_ends: HashSet<(usize, Partial<N>)>,
+    counted: HashMap<(usize, Partial<N>), u64>,
+}
+
+impl<'a, N: Number> PrecedenceSearch<'a, N> {
+    pub(crate) fn new(result: N, numbers: &'a [N], operators: &'a [&'a dyn Operator<N>]) -> Self {
+        Self {
+            result,
+            numbers,
+            operators,
+            ceiling: ceiling(result, numbers, operators),
+            dead_ends: HashSet::new(),
+            counted: HashMap::new(),
+        }
+    }
+
+    fn start(&self) -> Option<Partial<N>> {
+        Some(Partial {
+            pending: Vec::new(),
+            value: *self.numbers.first()?,
+        })
+    }
+
+    /// Follow `partial` with operator `op` and the next number, or `None` if that can't be worked
+    /// out.
+    fn step(&self, partial: &Partial<N>, op: usize, number: N) -> Option<Partial<N>> {
+        let mut partial = partial.clone();
+        partial.value = self.settle(&mut partial, self.operators[op].precedence())?;
+        if !fits(self.ceiling, partial.value) || !fits(self.ceiling, number) {
+            return None;
+        }
+        partial.pending.push((partial.value, op));
+        partial.value = number;
+        Some(partial)
+    }
+
+    /// Apply the pending operators which bind at least as tightly as `precedence`, returning the
+    /// value they give.
+    fn settle(&self, partial: &mut Partial<N>, precedence: u8) -> Option<N> {
+        let mut value = partial.value;
+        while let Some((lhs, op)) = partial.pending.last() {
+            if self.operators[*op].precedence() < precedence {
+                break;
+            }
+            value = self.operators[*op].apply(*lhs, value)?;
+            partial.pending.pop();
+        }
+        Some(value)
+    }
+
+    fn finish(&self, partial: &Partial<N>) -> Option<N> {
+        self.settle(&mut partial.clone(), 0)
+    }
+
+    pub(crate) fn is_solvable(&mut self) -> bool {
+        self.start().is_some_and(|start| self.reaches(1, &start))
+    }
+
+    /// Whether `partial`, which took the first `taken` numbers, can go on to give the result.
+    fn reaches(&mut self, taken: usize, partial: &Partial<N>) -> bool {
+        if taken == self.numbers.len() {
+            return self.finish(partial) == Some(self.result);
+        }
+        let key = (taken, partial.clone());
+        if self.dead_ends.contains(&key) {
+            return false;
+        }
+
+        let reached = (0..self.operators.len()).any(|op| {
+            self.step(partial, op, self.numbers[taken])
+                .is_some_and(|next| self.reaches(taken + 1, &next))
+        });
+        if !reached {
+            self.dead_ends.insert(key);
+        }
+        reached
+    }
+
+    pub(crate) fn count(&mut self) -> u64 {
+        self.start().map_or(0, |start| self.count_from(1, &start))
+    }
+
+    fn count_from(&mut self, taken: usize, partial: &Partial<N>) -> u64 {
+        if !self.reaches(taken, partial) {
+            return 0;
+        }
+        if taken == self.numbers.len() {
+            return 1;
+        }
+        let key = (taken, partial.clone());
+        if let Some(count) = self.counted.get(&key) {
+            return *count;
+        }
+
+        let mut count: u64 = 0;
+        for op in 0..self.operators.len() {
+            if let Some(next) = self.step(partial, op, self.numbers[taken]) {
+                count = count.saturating_add(self.count_from(taken + 1, &next));
+            }
+        }
+        self.counted.insert(key, count);
+        count
+    }
+
+    pub(crate) fn find(&mut self) -> Option<Vec<usize>> {
+        let mut partial = self.start()?;
+        if !self.reaches(1, &partial) {
+            return None;
+        }
+
+        let mut chosen = Vec::with_capacity(self.numbers.len() - 1);
+        for taken in 1..self.numbers.len() {
+            let (op, next) = (0..self.operators.len())
+                .find_map(|op| {
+                    let next = self.step(&partial, op, self.numbers[taken])?;
+                    self.reaches(taken + 1, &next).then_some((op, next))
+                })
+                .expect("The result can be reached, so some operator leads on towards it.");
+            chosen.push(op);
+            partial = next;
+        }
+        Some(chosen)
+    }
+
+    /// Every solution, in order.
+    pub(crate) fn solutions(&mut self) -> Vec<Vec<usize>> {
+        let mut found = Vec::new();
+        if let Some(start) = self.start() {
+            self.collect(1, &start, &mut Vec::new(), &mut found);
+        }
+        found
+    }
+
+    fn collect(
+        &mut self,
+        taken: usize,
+        partial: &Partial<N>,
+        chosen: &mut Vec<usize>,
+        found: &mut Vec<Vec<usize>>,
+    ) {
+        if !self.reaches(taken, partial) {
+            return;
+        }
+        if taken == self.numbers.len() {
+            found.push(chosen.clone());
+            return;
+        }
+        for op in 0..self.operators.len() {
+            if let Some(next) = self.step(partial, op, self.numbers[taken]) {
+                chosen.push(op);
+                self.collect(taken + 1, &next, chosen, found);
+                chosen.pop();
+            }
+        }
+    }
+}
+
+/// A search through every choice of operators, and every way of grouping the numbers, with
+/// [`Evaluation::AnyParentheses`].
+pub(crate) struct ParenthesesSearch<'a, N> {
+    result: N,
+    numbers: &'a [N],
+    operators: &'a [&'a dyn Operator<N>],
+    /// The biggest value worth grouping, from [`ceiling`].
+    ceiling: Option<N>,
+}
+
+impl<'a, N: Number> ParenthesesSearch<'a, N> {
+    pub(crate) fn new(result: N, numbers: &'a [N], operators: &'a [&'a dyn Operator<N>]) -> Self {
+        Self {
+            result,
+            numbers,
+            operators,
+            ceiling: ceiling(result, numbers, operators),
+        }
+    }
+
+    pub(crate) fn is_solvable(&self) -> bool {
+        self.find().is_some()
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        let mut count: u64 = 0;
+        self.each_solution(&mut |_| {
+            count = count.saturating_add(1);
+            true
+        });
+        count
+    }
+
+    pub(crate) fn find(&self) -> Option<Vec<usize>> {
+        let mut found = None;
+        self.each_solution(&mut |chosen| {
+            found = Some(chosen.to_vec());
+            false
+        });
+        found
+    }
+
+    /// Every solution, in order.
+    pub(crate) fn solutions(&self) -> Vec<Vec<usize>> {
+        let mut found = Vec::new();
+        self.each_solution(&mut |chosen| {
+            found.push(chosen.to_vec());
+            true
+        });
+        found
+    }
+
+    /// Call `visit` with every solution in order, for as long as it returns `true`.
+    fn each_solution(&self, visit: &mut dyn FnMut(&[usize]) -> bool) {
+        if self.numbers.is_empty() {
+            return;
+        }
+        // `values[j][i]` holds every value the numbers from `i` up to `j` can be grouped to give.
+        let mut values = vec![vec![HashSet::from([self.numbers[0]])]];
+        self.choose(&mut Vec::new(), &mut values, visit);
+    }
+
+    /// Choose the operator after the numbers in `values`, returning whether to carry on.
+    fn choose(
+        &self,
+        chosen: &mut Vec<usize>,
+        values: &mut Vec<Vec<HashSet<N>>>,
+        visit: &mut dyn FnMut(&[usize]) -> bool,
+    ) -> bool {
+        let end = values.len();
+        if end == self.numbers.len() {
+            return !values[end - 1][0].contains(&self.result) || visit(chosen);
+        }
+
+        for op in 0..self.operators.len() {
+            chosen.push(op);
+            values.push(self.group(chosen, values));
+            let carry_on = self.choose(chosen, values, visit);
+            values.pop();
+            chosen.pop();
+            if !carry_on {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Every value the numbers from each `i` up to the next one can be grouped to give, with the
+    /// operators in `chosen`.
+    fn group(&self, chosen: &[usize], values: &[Vec<HashSet<N>>]) -> Vec<HashSet<N>> {
+        let end = values.len();
+        let mut column = vec![HashSet::new(); end + 1];
+        column[end].insert(self.numbers[end]);
+        for start in (0..end).rev() {
+            let mut grouped = HashSet::new();
+            // The last operator applied is the one after number `split`.
+            for split in start..end {
+                let operator = self.operators[chosen[split]];
+                for lhs in values[split][start].iter() {
+                    for rhs in column[split + 1].iter() {
+                        grouped.extend(
+                            operator
+                                .apply(*lhs, *rhs)
+                                .filter(|value| fits(self.ceiling, *value)),
+                        );
+                    }
+                }
+            }
+            column[start] = grouped;
+        }
+        column
+    }
+
+    /// Write out the solution `chosen` with parentheses showing how the numbers are grouped,
+    /// like `(81 + 40) * 27`, or `None` if no grouping gives the result.
+    pub(crate) fn render(&self, chosen: &[usize]) -> Option<String> {
+        let mut values = vec![vec![HashSet::from([*self.numbers.first()?])]];
+        for end in 1..self.numbers.len() {
+            let column = self.group(&chosen[..end], &values);
+            values.push(column);
+        }
+        self.render_group(chosen, &values, 0, self.numbers.len() - 1, self.result)
+    }
+
+    /// Write out the numbers from `start` up to `end`, grouped to give `value`.
+    fn render_group(
+        &self,
+        chosen: &[usize],
+        values: &[Vec<HashSet<N>>],
+        start: usize,
+        end: usize,
+        value: N,
+    ) -> Option<String> {
+        if start == end {
+            return (self.numbers[start] == value).then(|| value.to_string());
+        }
+        if !values[end][start].contains(&value) {
+            return None;
+        }
+
+        (start..end).find_map(|split| {
+            let operator = self.operators[chosen[split]];
+            values[split][start].iter().find_map(|lhs| {
+                values[end][split + 1].iter().find_map(|rhs| {
+                    if operator.apply(*lhs, *rhs) != Some(value) {
+                        return None;
+                    }
+                    let lhs = self.render_group(chosen, values, start, split, *lhs)?;
+                    let rhs = self.render_group(chosen, values, split + 1, end, *rhs)?;
+                    let wrap = |text: String, single: bool| {
+                        if single {
+                            text
+                        } else {
+                            format!("({text})")
+                        }
+                    };
+                    Some(format!(
+                        "{} {} {}",
+                        wrap(lhs, split == start),
+                        operator.symbol(),
+                        wrap(rhs, split + 1 == end)
+                    ))
+                })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::operator::OperatorSet;
+
+    #[rstest]
+    #[case("left-to-right", Evaluation::LeftToRight)]
+    #[case("precedence", Evaluation::Precedence)]
+    #[case("parentheses", Evaluation::AnyParentheses)]
+    fn test_from_str(#[case] name: &str, #[case] expected: Evaluation) {
+        assert_eq!(name.parse(), Ok(expected));
+    }
+
+    #[rstest]
+    #[case(3267, &[81, 40, 27], &[1, 0], Some("(81 * 40) + 27"))]
+    #[case(1161, &[81, 40, 27], &[0, 1], Some("81 + (40 * 27)"))]
+    #[case(3267, &[81, 40, 27], &[0, 1], Some("(81 + 40) * 27"))]
+    #[case(3268, &[81, 40, 27], &[0, 1], None)]
+    #[case(20, &[2, 3, 2, 2], &[0, 1, 1], Some("(2 + 3) * (2 * 2)"))]
+    fn test_render(
+        #[case] result: u64,
+        #[case] numbers: &[u64],
+        #[case] chosen: &[usize],
+        #[case] expected: Option<&str>,
+    ) {
+        let set = OperatorSet::add_multiply();
+        let operators: Vec<&dyn Operator> = set.iter().collect();
+        let search = ParenthesesSearch::new(result, numbers, &operators);
+
+        assert_eq!(search.render(chosen).as_deref(), expected);
+    }
+}
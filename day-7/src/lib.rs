@@ -1,2 +1,32 @@
+use aoc_common::{Solution, SolveError};
+
+pub mod equation;
+pub mod evaluation;
+pub mod number;
+pub mod operator;
 pub mod part1;
 pub mod part2;
+pub mod report;
+pub mod solver;
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Answer = usize;
+
+    fn day(&self) -> u8 {
+        7
+    }
+
+    fn description(&self) -> &'static str {
+        include_str!("../daily_description.txt")
+    }
+
+    fn try_part1(&self, input: &str) -> Result<usize, SolveError> {
+        part1::try_process(input)
+    }
+
+    fn try_part2(&self, input: &str) -> Result<usize, SolveError> {
+        part2::try_process(input)
+    }
+}
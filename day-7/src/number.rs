@@ -0,0 +1,77 @@
+//! The unsigned integers equations can be solved with.
+//!
+//! Everything is solved with `u64` unless asked otherwise, which fits the puzzle input. Equations
+//! whose results or intermediate values don't fit can be solved with `u128` instead. All
+//! arithmetic is checked, so a value which doesn't fit is a dead end rather than a wrong answer.
+
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::{Div, Rem},
+};
+
+use nom::{character::complete, IResult};
+
+/// An unsigned integer type the solver can work with.
+pub trait Number:
+    Copy
+    + Ord
+    + Hash
+    + Debug
+    + Display
+    + Send
+    + Sync
+    + From<u64>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + 'static
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_pow(self, exponent: u32) -> Option<Self>;
+
+    /// The number as an exponent, if it is small enough to be one.
+    fn to_exponent(self) -> Option<u32>;
+
+    /// Parse a number in decimal.
+    fn parse(input: &str) -> IResult<&str, Self>;
+}
+
+macro_rules! impl_number {
+    ($($ty:ty => $parser:path),*) => {$(
+        impl Number for $ty {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_sub(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_mul(self, rhs)
+            }
+
+            fn checked_pow(self, exponent: u32) -> Option<Self> {
+                <$ty>::checked_pow(self, exponent)
+            }
+
+            fn to_exponent(self) -> Option<u32> {
+                u32::try_from(self).ok()
+            }
+
+            fn parse(input: &str) -> IResult<&str, Self> {
+                $parser(input)
+            }
+        }
+    )*};
+}
+
+impl_number!(u64 => complete::u64, u128 => complete::u128);
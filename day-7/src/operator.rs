@@ -0,0 +1,373 @@
+//! The operators which can go between the numbers of an equation, and choosing which ones to use.
+//!
+//! The solver only knows operators through the [`Operator`] trait, so new ones can be added
+//! without touching it, either as a [`Builtin`] or by implementing the trait.
+
+use std::{borrow::Cow, fmt::Display, str::FromStr, sync::Arc};
+
+use crate::number::Number;
+
+/// A binary operator on the numbers of an equation, which are `N`s.
+pub trait Operator<N: Number = u64>: std::fmt::Debug + Send + Sync {
+    /// How the operator is written between two numbers, like `+`.
+    fn symbol(&self) -> Cow<'_, str>;
+
+    /// Work out `lhs <op> rhs`, or `None` if it isn't defined or doesn't fit in an `N`.
+    fn apply(&self, lhs: N, rhs: N) -> Option<N>;
+
+    /// Find every left-hand side `lhs` for which `lhs <op> rhs` is `result`.
+    ///
+    /// The solver works backwards from the result, so this has to be exact: answering
+    /// [`Undo::Lhs`] when more than one left-hand side fits makes it miss solutions.
+    fn undo(&self, result: N, rhs: N) -> Undo<N>;
+
+    /// How tightly the operator binds with [`Evaluation::Precedence`], where operators with a
+    /// higher precedence are applied first. Operators with the same precedence are applied left
+    /// to right.
+    ///
+    /// [`Evaluation::Precedence`]: crate::evaluation::Evaluation::Precedence
+    fn precedence(&self) -> u8 {
+        1
+    }
+
+    /// Whether `lhs <op> rhs` is never less than either `lhs` or `rhs` when both are at least 1,
+    /// like `+` but unlike `-`.
+    ///
+    /// When every operator grows like this, a value bigger than the result can never come back
+    /// down to it, so the solver stops looking at it.
+    fn keeps_growing(&self) -> bool {
+        false
+    }
+}
+
+/// Which left-hand sides an operator gives `result` for, from [`Operator::undo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Undo<N = u64> {
+    /// No left-hand side gives the result.
+    Impossible,
+    /// Only this left-hand side gives the result.
+    Lhs(N),
+    /// Every left-hand side gives the result, like multiplying by 0 gives 0.
+    AnyLhs,
+}
+
+/// The operators which come with the solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    /// `+`
+    Add,
+    /// `-`, as long as the result isn't negative.
+    Subtract,
+    /// `*`
+    Multiply,
+    /// `/`, as long as it divides exactly.
+    Divide,
+    /// `^`, raising the left-hand side to the power of the right-hand side.
+    Power,
+    /// `||`: join the digits of both numbers, so `12 || 345` is `12345`.
+    ///
+    /// The digits are written in `base`, which is 10 for `||`. Other bases are written with the
+    /// base after the bars, so in `||2`, `2 || 1` is `0b10 || 0b1`, which is `0b101` or 5.
+    Concatenate { base: u64 },
+}
+
+impl Builtin {
+    /// `||`, joining decimal digits.
+    pub const CONCATENATE: Builtin = Builtin::Concatenate { base: 10 };
+
+    /// Find the operator written as `symbol`, as in [`Operator::symbol`].
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "+" => Some(Builtin::Add),
+            "-" => Some(Builtin::Subtract),
+            "*" => Some(Builtin::Multiply),
+            "/" => Some(Builtin::Divide),
+            "^" => Some(Builtin::Power),
+            "||" => Some(Builtin::CONCATENATE),
+            _ => {
+                let base = symbol.strip_prefix("||")?.parse().ok()?;
+                (base >= 2).then_some(Builtin::Concatenate { base })
+            }
+        }
+    }
+}
+
+impl<N: Number> Operator<N> for Builtin {
+    fn symbol(&self) -> Cow<'_, str> {
+        match self {
+            Builtin::Add => "+".into(),
+            Builtin::Subtract => "-".into(),
+            Builtin::Multiply => "*".into(),
+            Builtin::Divide => "/".into(),
+            Builtin::Power => "^".into(),
+            Builtin::Concatenate { base: 10 } => "||".into(),
+            Builtin::Concatenate { base } => format!("||{base}").into(),
+        }
+    }
+
+    fn apply(&self, lhs: N, rhs: N) -> Option<N> {
+        match self {
+            Builtin::Add => lhs.checked_add(rhs),
+            Builtin::Subtract => lhs.checked_sub(rhs),
+            Builtin::Multiply => lhs.checked_mul(rhs),
+            Builtin::Divide => (rhs != N::ZERO && lhs % rhs == N::ZERO).then(|| lhs / rhs),
+            Builtin::Power => lhs.checked_pow(rhs.to_exponent()?),
+            Builtin::Concatenate { base } => match shift(rhs, *base) {
+                Some(shift) => lhs.checked_mul(shift)?.checked_add(rhs),
+                None => (lhs == N::ZERO).then_some(rhs),
+            },
+        }
+    }
+
+    fn undo(&self, result: N, rhs: N) -> Undo<N> {
+        let lhs = match self {
+            Builtin::Add => result.checked_sub(rhs),
+            Builtin::Subtract => result.checked_add(rhs),
+            Builtin::Multiply if rhs == N::ZERO => return any_lhs_if(result == N::ZERO),
+            Builtin::Multiply => (result % rhs == N::ZERO).then(|| result / rhs),
+            Builtin::Divide if rhs == N::ZERO => None,
+            Builtin::Divide => result.checked_mul(rhs),
+            Builtin::Power if rhs == N::ZERO => return any_lhs_if(result == N::ONE),
+            Builtin::Power => root(result, rhs),
+            Builtin::Concatenate { base } => match shift(rhs, *base) {
+                Some(shift) => (result % shift == rhs).then_some(result / shift),
+                // Joining anything but 0 in front of `rhs` would overflow.
+                None => (result == rhs).then_some(N::ZERO),
+            },
+        };
+        lhs.map_or(Undo::Impossible, Undo::Lhs)
+    }
+
+    /// `+` and `-` bind loosest, then `*` and `/`, then `^`, and `||` joins digits before
+    /// anything else.
+    fn precedence(&self) -> u8 {
+        match self {
+            Builtin::Add | Builtin::Subtract => 1,
+            Builtin::Multiply | Builtin::Divide => 2,
+            Builtin::Power => 3,
+            Builtin::Concatenate { .. } => 4,
+        }
+    }
+
+    /// `-` and `/` make numbers smaller, and so does `^` with 1 on the left, as `1 ^ 5` is 1.
+    fn keeps_growing(&self) -> bool {
+        match self {
+            Builtin::Add | Builtin::Multiply | Builtin::Concatenate { .. } => true,
+            Builtin::Subtract | Builtin::Divide | Builtin::Power => false,
+        }
+    }
+}
+
+impl Display for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Operator::<u64>::symbol(self))
+    }
+}
+
+fn any_lhs_if<N>(condition: bool) -> Undo<N> {
+    if condition {
+        Undo::AnyLhs
+    } else {
+        Undo::Impossible
+    }
+}
+
+/// What to multiply a number by to make room for the digits of `number` in `base`, or `None` if
+/// that doesn't fit in an `N`.
+fn shift<N: Number>(number: N, base: u64) -> Option<N> {
+    let base = N::from(base);
+    let mut shift = base;
+    while shift <= number {
+        shift = shift.checked_mul(base)?;
+    }
+    Some(shift)
+}
+
+/// The whole number whose `exponent`th power is `number`, if there is one.
+fn root<N: Number>(number: N, exponent: N) -> Option<N> {
+    let exponent = exponent.to_exponent()?;
+    if exponent == 1 {
+        return Some(number);
+    }
+    // Search for the smallest whole number whose power is at least `number`, counting powers
+    // too big to fit as more than it.
+    let (mut low, mut high) = (N::ZERO, number);
+    while low < high {
+        let middle = low.checked_add(high.checked_sub(low)? / N::from(2))?;
+        match middle.checked_pow(exponent) {
+            Some(power) if power < number => low = middle.checked_add(N::ONE)?,
+            _ => high = middle,
+        }
+    }
+    (low.checked_pow(exponent) == Some(number)).then_some(low)
+}
+
+/// The operators a solver may choose from, in the order solutions are listed.
+#[derive(Debug, Clone)]
+pub struct OperatorSet<N: Number = u64> {
+    operators: Vec<Arc<dyn Operator<N>>>,
+}
+
+impl<N: Number> Default for OperatorSet<N> {
+    fn default() -> Self {
+        Self {
+            operators: Vec::new(),
+        }
+    }
+}
+
+impl<N: Number> OperatorSet<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `+` and `*`, for the first part of the puzzle.
+    pub fn add_multiply() -> Self {
+        Self::new().with(Builtin::Add).with(Builtin::Multiply)
+    }
+
+    /// `+`, `*` and `||`, for the second part of the puzzle.
+    pub fn add_multiply_concatenate() -> Self {
+        Self::add_multiply().with(Builtin::CONCATENATE)
+    }
+
+    /// Add `operator` after the others.
+    pub fn with(mut self, operator: impl Operator<N> + 'static) -> Self {
+        self.push(operator);
+        self
+    }
+
+    /// Add `operator` after the others.
+    pub fn push(&mut self, operator: impl Operator<N> + 'static) {
+        self.operators.push(Arc::new(operator));
+    }
+
+    pub fn len(&self) -> usize {
+        self.operators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operators.is_empty()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&dyn Operator<N>> {
+        self.operators.get(idx).map(|operator| operator.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Operator<N>> {
+        self.operators.iter().map(|operator| operator.as_ref())
+    }
+}
+
+/// An operator symbol which isn't one of the [`Builtin`] operators.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnknownOperator {
+    pub symbol: String,
+}
+
+impl Display for UnknownOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown operator {:?}", self.symbol)
+    }
+}
+
+impl std::error::Error for UnknownOperator {}
+
+impl<N: Number> FromStr for OperatorSet<N> {
+    type Err = UnknownOperator;
+
+    /// Read the [`Builtin`] operators written as symbols separated by whitespace, like `+ * ||`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_whitespace()
+            .map(|symbol| {
+                Builtin::from_symbol(symbol).ok_or_else(|| UnknownOperator {
+                    symbol: symbol.to_string(),
+                })
+            })
+            .try_fold(Self::new(), |set, operator| Ok(set.with(operator?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use Builtin::{Add, Divide, Multiply, Power, Subtract};
+
+    #[rstest]
+    #[case(Add, 15, 6, Undo::Lhs(9))]
+    #[case(Add, 5, 6, Undo::Impossible)]
+    #[case(Subtract, 5, 6, Undo::Lhs(11))]
+    #[case(Subtract, u64::MAX, 1, Undo::Impossible)]
+    #[case(Multiply, 156, 6, Undo::Lhs(26))]
+    #[case(Multiply, 157, 6, Undo::Impossible)]
+    #[case(Multiply, 0, 0, Undo::AnyLhs)]
+    #[case(Multiply, 1, 0, Undo::Impossible)]
+    #[case(Divide, 4, 3, Undo::Lhs(12))]
+    #[case(Divide, 4, 0, Undo::Impossible)]
+    #[case(Power, 1, 0, Undo::AnyLhs)]
+    #[case(Power, 2, 0, Undo::Impossible)]
+    #[case(Power, 243, 5, Undo::Lhs(3))]
+    #[case(Power, 244, 5, Undo::Impossible)]
+    #[case(Power, u64::MAX, 1, Undo::Lhs(u64::MAX))]
+    #[case(Power, 1 << 63, 63, Undo::Lhs(2))]
+    #[case(Power, 4611686018427387907, 1, Undo::Lhs(4611686018427387907))]
+    #[case(Power, 18446744065119617025, 2, Undo::Lhs(4294967295))]
+    #[case(Power, 18446744065119617026, 2, Undo::Impossible)]
+    #[case(Power, u64::MAX, 2, Undo::Impossible)]
+    #[case(Power, 18446724184312856125, 3, Undo::Lhs(2642245))]
+    #[case(Power, 18446724184312856124, 3, Undo::Impossible)]
+    #[case(Builtin::CONCATENATE, 156, 6, Undo::Lhs(15))]
+    #[case(Builtin::CONCATENATE, 156, 56, Undo::Lhs(1))]
+    #[case(Builtin::CONCATENATE, 156, 156, Undo::Lhs(0))]
+    #[case(Builtin::CONCATENATE, 156, 7, Undo::Impossible)]
+    #[case(Builtin::CONCATENATE, 56, 156, Undo::Impossible)]
+    #[case(Builtin::CONCATENATE, u64::MAX, u64::MAX, Undo::Lhs(0))]
+    #[case(Builtin::Concatenate { base: 2 }, 0b101, 0b1, Undo::Lhs(0b10))]
+    #[case(Builtin::Concatenate { base: 2 }, 0b101, 0b11, Undo::Impossible)]
+    fn test_undo(
+        #[case] operator: Builtin,
+        #[case] result: u64,
+        #[case] rhs: u64,
+        #[case] expected: Undo,
+    ) {
+        assert_eq!(operator.undo(result, rhs), expected);
+        if let Undo::Lhs(lhs) = expected {
+            assert_eq!(operator.apply(lhs, rhs), Some(result));
+        }
+    }
+
+    #[rstest]
+    #[case(Add, 1, 2, Some(3))]
+    #[case(Subtract, 1, 2, None)]
+    #[case(Divide, 7, 2, None)]
+    #[case(Power, 2, 64, None)]
+    #[case(Builtin::CONCATENATE, 12, 345, Some(12345))]
+    #[case(Builtin::CONCATENATE, 0, 0, Some(0))]
+    #[case(Builtin::CONCATENATE, 1, u64::MAX, None)]
+    #[case(Builtin::Concatenate { base: 16 }, 0xab, 0xcd, Some(0xabcd))]
+    fn test_apply(
+        #[case] operator: Builtin,
+        #[case] lhs: u64,
+        #[case] rhs: u64,
+        #[case] expected: Option<u64>,
+    ) {
+        assert_eq!(operator.apply(lhs, rhs), expected);
+    }
+
+    #[test]
+    fn operator_sets_are_read_from_symbols() {
+        let set: OperatorSet = "+ - * / ^ || ||2".parse().unwrap();
+        let symbols: Vec<_> = set.iter().map(|operator| operator.symbol()).collect();
+
+        assert_eq!(symbols, ["+", "-", "*", "/", "^", "||", "||2"]);
+        assert_eq!(
+            "+ % *".parse::<OperatorSet>().unwrap_err(),
+            UnknownOperator {
+                symbol: "%".to_string()
+            }
+        );
+        assert!("||1".parse::<OperatorSet>().is_err());
+    }
+}
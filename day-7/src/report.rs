@@ -0,0 +1,188 @@
+//! Reporting what the solver makes of each line of a calibration file, for checking by hand.
+
+use std::fmt::Display;
+
+use aoc_common::SolveError;
+
+use crate::{
+    equation::{parse_full_line, Equation},
+    evaluation::Evaluation,
+    number::Number,
+    operator::OperatorSet,
+    solver::{count_solutions, find_solution, parenthesize, solutions},
+};
+
+/// How many of the solutions to write out in a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Show {
+    /// One solution, found without looking for the others.
+    #[default]
+    One,
+    /// Every solution, in order.
+    All,
+}
+
+/// What the solver found for one line of a calibration file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Report<N = u64> {
+    /// The line of the input the equation was read from, starting at 1.
+    pub line: usize,
+    pub equation: Equation<N>,
+    /// How many choices of operators make the equation true, stopping at `u64::MAX`.
+    pub solutions: u64,
+    /// The equation written out with the operators of the solutions shown, like `81 + 40 * 27`.
+    pub expressions: Vec<String>,
+}
+
+impl<N: Number> Report<N> {
+    /// Check a single equation, working it out in the order given by `evaluation`.
+    pub fn new(
+        line: usize,
+        equation: Equation<N>,
+        operators: &OperatorSet<N>,
+        evaluation: Evaluation,
+        show: Show,
+    ) -> Self {
+        let (result, numbers) = (equation.result, &equation.numbers);
+        let found = match show {
+            Show::One => find_solution(result, numbers, operators, evaluation)
+                .into_iter()
+                .collect(),
+            Show::All => solutions(result, numbers, operators, evaluation),
+        };
+        let expressions = found
+            .iter()
+            .map(|found| match evaluation {
+                // Show how the numbers are grouped, as the operators alone don't say.
+                Evaluation::AnyParentheses => parenthesize(result, numbers, found)
+                    .expect("Every solution has a grouping which gives the result."),
+                Evaluation::LeftToRight | Evaluation::Precedence => equation.render(found),
+            })
+            .collect();
+
+        Self {
+            line,
+            solutions: count_solutions(result, numbers, operators, evaluation),
+            expressions,
+            equation,
+        }
+    }
+
+    pub fn is_solvable(&self) -> bool {
+        self.solutions > 0
+    }
+}
+
+impl<N: Number> Display for Report<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {} has ", self.line, self.equation)?;
+        match self.solutions {
+            0 => write!(f, "no solutions")?,
+            1 => write!(f, "1 solution")?,
+            u64::MAX => write!(f, "at least {} solutions", u64::MAX)?,
+            solutions => write!(f, "{solutions} solutions")?,
+        }
+        for expression in self.expressions.iter() {
+            write!(f, "\n  {} = {expression}", self.equation.result)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check every equation in the input, skipping blank lines.
+pub fn report<N: Number>(
+    input: &str,
+    operators: &OperatorSet<N>,
+    evaluation: Evaluation,
+    show: Show,
+) -> Result<Vec<Report<N>>, SolveError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(idx, line)| {
+            let equation = parse_full_line(input, line)?;
+            Ok(Report::new(idx + 1, equation, operators, evaluation, show))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    const EXAMPLE: &str = "190: 10 19
+3267: 81 40 27
+83: 17 5
+
+292: 11 6 16 20";
+
+    #[rstest]
+    #[case(
+        Show::One,
+        "line 1: 190: 10 19 has 1 solution
+  190 = 10 * 19
+line 2: 3267: 81 40 27 has 2 solutions
+  3267 = 81 * 40 + 27
+line 3: 83: 17 5 has no solutions
+line 5: 292: 11 6 16 20 has 1 solution
+  292 = 11 + 6 * 16 + 20"
+    )]
+    #[case(
+        Show::All,
+        "line 1: 190: 10 19 has 1 solution
+  190 = 10 * 19
+line 2: 3267: 81 40 27 has 2 solutions
+  3267 = 81 + 40 * 27
+  3267 = 81 * 40 + 27
+line 3: 83: 17 5 has no solutions
+line 5: 292: 11 6 16 20 has 1 solution
+  292 = 11 + 6 * 16 + 20"
+    )]
+    fn test_report(#[case] show: Show, #[case] expected: &str) {
+        let reports: Vec<Report> = report(
+            EXAMPLE,
+            &OperatorSet::add_multiply(),
+            Evaluation::LeftToRight,
+            show,
+        )
+        .unwrap();
+        let written: Vec<String> = reports.iter().map(|report| report.to_string()).collect();
+
+        assert_eq!(written.join("\n"), expected);
+        let solvable: Vec<bool> = reports.iter().map(Report::is_solvable).collect();
+        assert_eq!(solvable, [true, true, false, true]);
+    }
+
+    #[rstest]
+    #[case(
+        Evaluation::LeftToRight,
+        "line 1: 3267: 81 40 27 has 2 solutions
+  3267 = 81 + 40 * 27
+  3267 = 81 * 40 + 27"
+    )]
+    #[case(
+        Evaluation::Precedence,
+        "line 1: 3267: 81 40 27 has 1 solution
+  3267 = 81 * 40 + 27"
+    )]
+    #[case(
+        Evaluation::AnyParentheses,
+        "line 1: 3267: 81 40 27 has 2 solutions
+  3267 = (81 + 40) * 27
+  3267 = (81 * 40) + 27"
+    )]
+    fn reports_follow_the_evaluation_order(#[case] evaluation: Evaluation, #[case] expected: &str) {
+        let reports: Vec<Report> = report(
+            "3267: 81 40 27",
+            &OperatorSet::add_multiply(),
+            evaluation,
+            Show::All,
+        )
+        .unwrap();
+
+        assert_eq!(reports[0].to_string(), expected);
+    }
+}
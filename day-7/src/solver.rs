@@ -0,0 +1,671 @@
+//! Finding the operators which make an equation true, working backwards from its result.
+//!
+//! This is how equations worked out with [`Evaluation::LeftToRight`] are solved. The other orders
+//! are searched as described in [`crate::evaluation`].
+//!
+//! Operators are evaluated left to right, so the last operator is the last one applied. Undoing it
+//! from the result gives what the numbers before it must add up to, and most operators can only
+//! be undone when the result fits: `*` needs the result to be divisible by the last number, and
+//! `||` needs the result to end in its digits. Walking back like this cuts off almost every dead
+//! end after a step or two, instead of trying every combination of operators.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops::ControlFlow,
+};
+
+use crate::{
+    evaluation::{Evaluation, ParenthesesSearch, PrecedenceSearch},
+    number::Number,
+    operator::{Operator, OperatorSet, Undo},
+};
+
+/// Whether some choice of `operators` between `numbers` gives `result`, worked out in the order
+/// given by `evaluation`.
+pub fn is_solvable<N: Number>(
+    result: N,
+    numbers: &[N],
+    operators: &OperatorSet<N>,
+    evaluation: Evaluation,
+) -> bool {
+    let operators: Vec<&dyn Operator<N>> = operators.iter().collect();
+    match evaluation {
+        Evaluation::LeftToRight => Search::new(numbers, &operators).reaches(numbers.len(), result),
+        Evaluation::Precedence => PrecedenceSearch::new(result, numbers, &operators).is_solvable(),
+        Evaluation::AnyParentheses => {
+            ParenthesesSearch::new(result, numbers, &operators).is_solvable()
+        }
+    }
+}
+
+/// Every choice of `operators` between `numbers` which gives `result`.
+///
+/// The solutions are ordered as if the operators were digits, in the order they are in the set.
+pub fn solutions<'a, N: Number>(
+    result: N,
+    numbers: &[N],
+    operators: &'a OperatorSet<N>,
+    evaluation: Evaluation,
+) -> Vec<Vec<&'a dyn Operator<N>>> {
+    let listed: Vec<&dyn Operator<N>> = operators.iter().collect();
+    let mut found = match evaluation {
+        Evaluation::LeftToRight => {
+            let mut search = Search::new(numbers, &listed);
+            let mut found = Vec::new();
+            if search.reaches(numbers.len(), result) {
+                let mut chosen = Vec::with_capacity(numbers.len() - 1);
+                search.collect(numbers.len(), result, &mut chosen, &mut found);
+            }
+            found
+        }
+        Evaluation::Precedence => PrecedenceSearch::new(result, numbers, &listed).solutions(),
+        Evaluation::AnyParentheses => ParenthesesSearch::new(result, numbers, &listed).solutions(),
+    };
+    found.sort();
+
+    found
+        .into_iter()
+        .map(|solution| by_position(operators, solution))
+        .collect()
+}
+
+/// How many choices of `operators` between `numbers` give `result`, without listing them.
+///
+/// Stops counting at `u64::MAX`.
+pub fn count_solutions<N: Number>(
+    result: N,
+    numbers: &[N],
+    operators: &OperatorSet<N>,
+    evaluation: Evaluation,
+) -> u64 {
+    let operators: Vec<&dyn Operator<N>> = operators.iter().collect();
+    match evaluation {
+        Evaluation::LeftToRight => {
+            let mut counted = HashMap::new();
+            Search::new(numbers, &operators).count(numbers.len(), result, &mut counted)
+        }
+        Evaluation::Precedence => PrecedenceSearch::new(result, numbers, &operators).count(),
+        Evaluation::AnyParentheses => ParenthesesSearch::new(result, numbers, &operators).count(),
+    }
+}
+
+/// One choice of `operators` between `numbers` which gives `result`, found without looking for
+/// the others.
+pub fn find_solution<'a, N: Number>(
+    result: N,
+    numbers: &[N],
+    operators: &'a OperatorSet<N>,
+    evaluation: Evaluation,
+) -> Option<Vec<&'a dyn Operator<N>>> {
+    let listed: Vec<&dyn Operator<N>> = operators.iter().collect();
+    let found = match evaluation {
+        Evaluation::LeftToRight => {
+            let mut search = Search::new(numbers, &listed);
+            let mut found = Vec::with_capacity(numbers.len().saturating_sub(1));
+            if !search.find(numbers.len(), result, &mut found) {
+                return None;
+            }
+            found.reverse();
+            found
+        }
+        Evaluation::Precedence => PrecedenceSearch::new(result, numbers, &listed).find()?,
+        Evaluation::AnyParentheses => ParenthesesSearch::new(result, numbers, &listed).find()?,
+    };
+
+    Some(by_position(operators, found))
+}
+
+/// Write out `operators` between `numbers`, with parentheses showing a way of grouping them which
+/// gives `result`, like `(81 + 40) * 27`. Returns `None` if no grouping gives the result.
+pub fn parenthesize<N: Number>(
+    result: N,
+    numbers: &[N],
+    operators: &[&dyn Operator<N>],
+) -> Option<String> {
+    let positions: Vec<usize> = (0..operators.len()).collect();
+    ParenthesesSearch::new(result, numbers, operators).render(&positions)
+}
+
+/// Look up the operators of a solution by where they are in the set.
+fn by_position<N: Number>(
+    operators: &OperatorSet<N>,
+    solution: Vec<usize>,
+) -> Vec<&dyn Operator<N>> {
+    solution
+        .into_iter()
+        .map(|idx| {
+            operators
+                .get(idx)
+                .expect("Only operators in the set are chosen.")
+        })
+        .collect()
+}
+
+/// A search backwards through the numbers of an equation.
+///
+/// Operators are referred to by where they are in the set.
+struct Search<'a, N> {
+    numbers: &'a [N],
+    operators: &'a [&'a dyn Operator<N>],
+    /// The results which the first so many numbers can't give, as `(count, result)`.
+    ///
+    /// Different choices for the later operators often undo to the same result, like `* 1` and
+    /// `+ 0`, so remembering the dead ends keeps long equations from taking exponential time.
+    dead_ends: HashSet<(usize, N)>,
+}
+
+impl<'a, N: Number> Search<'a, N> {
+    fn new(numbers: &'a [N], operators: &'a [&'a dyn Operator<N>]) -> Self {
+        Self {
+            numbers,
+            operators,
+            dead_ends: HashSet::new(),
+        }
+    }
+
+    /// Whether the first `count` numbers can give `result`.
+    fn reaches(&mut self, count: usize, result: N) -> bool {
+        match count {
+            0 => return false,
+            1 => return result == self.numbers[0],
+            _ => {}
+        }
+        if self.dead_ends.contains(&(count, result)) {
+            return false;
+        }
+
+        let last = self.numbers[count - 1];
+        let reached =
+            (0..self.operators.len()).any(|idx| match self.operators[idx].undo(result, last) {
+                Undo::Impossible => false,
+                Undo::Lhs(lhs) => self.reaches(count - 1, lhs),
+                Undo::AnyLhs => self
+                    .evaluations(count - 1, &mut Vec::new(), &mut |_| ControlFlow::Break(()))
+                    .is_break(),
+            });
+        if !reached {
+            self.dead_ends.insert((count, result));
+        }
+        reached
+    }
+
+    /// Add every solution for the first `count` numbers to `found`, each followed by `chosen`
+    /// reversed, which holds the operators already undone from the end.
+    ///
+    /// The first `count` numbers must be able to give `result`.
+    fn collect(
+        &mut self,
+        count: usize,
+        result: N,
+        chosen: &mut Vec<usize>,
+        found: &mut Vec<Vec<usize>>,
+    ) {
+        if count == 1 {
+            found.push(chosen.iter().rev().copied().collect());
+            return;
+        }
+
+        let last = self.numbers[count - 1];
+        for idx in 0..self.operators.len() {
+            chosen.push(idx);
+            match self.operators[idx].undo(result, last) {
+                Undo::Impossible => {}
+                Undo::Lhs(lhs) => {
+                    if self.reaches(count - 1, lhs) {
+                        self.collect(count - 1, lhs, chosen, found);
+                    }
+                }
+                Undo::AnyLhs => {
+                    // Any way of working out the numbers before will do.
+                    let _ = self.evaluations(count - 1, &mut Vec::new(), &mut |before| {
+                        found.push(before.iter().chain(chosen.iter().rev()).copied().collect());
+                        ControlFlow::Continue(())
+                    });
+                }
+            }
+            chosen.pop();
+        }
+    }
+
+    /// Count the solutions for the first `count` numbers, remembering them in `counted`.
+    fn count(&mut self, count: usize, result: N, counted: &mut HashMap<(usize, N), u64>) -> u64 {
+        if !self.reaches(count, result) {
+            return 0;
+        }
+        if count == 1 {
+            return 1;
+        }
+        if let Some(solutions) = counted.get(&(count, result)) {
+            return *solutions;
+        }
+
+        let last = self.numbers[count - 1];
+        let mut solutions: u64 = 0;
+        for idx in 0..self.operators.len() {
+            let more = match self.operators[idx].undo(result, last) {
+                Undo::Impossible => 0,
+                Undo::Lhs(lhs) => self.count(count - 1, lhs, counted),
+                Undo::AnyLhs => self.count_evaluations(count - 1),
+            };
+            solutions = solutions.saturating_add(more);
+        }
+        counted.insert((count, result), solutions);
+        solutions
+    }
+
+    /// Count the choices of operators for the first `count` numbers which can be worked out.
+    ///
+    /// Choices which get to the same value along the way are counted together.
+    fn count_evaluations(&self, count: usize) -> u64 {
+        let mut values = HashMap::from([(self.numbers[0], 1_u64)]);
+        for number in &self.numbers[1..count] {
+            let mut next: HashMap<N, u64> = HashMap::new();
+            for (value, ways) in values {
+                for operator in self.operators.iter() {
+                    if let Some(value) = operator.apply(value, *number) {
+                        let total = next.entry(value).or_default();
+                        *total = total.saturating_add(ways);
+                    }
+                }
+            }
+            values = next;
+        }
+        values
+            .into_values()
+            .fold(0, |total: u64, ways| total.saturating_add(ways))
+    }
+
+    /// Find one solution for the first `count` numbers, pushing its operators to `chosen` from
+    /// the last to the first.
+    fn find(&mut self, count: usize, result: N, chosen: &mut Vec<usize>) -> bool {
+        if !self.reaches(count, result) {
+            return false;
+        }
+        if count == 1 {
+            return true;
+        }
+
+        let last = self.numbers[count - 1];
+        for idx in 0..self.operators.len() {
+            match self.operators[idx].undo(result, last) {
+                Undo::Impossible => {}
+                Undo::Lhs(lhs) => {
+                    if self.reaches(count - 1, lhs) {
+                        chosen.push(idx);
+                        return self.find(count - 1, lhs, chosen);
+                    }
+                }
+                Undo::AnyLhs => {
+                    let mut found = None;
+                    let _ = self.evaluations(count - 1, &mut Vec::new(), &mut |before| {
+                        found = Some(before.to_vec());
+                        ControlFlow::Break(())
+                    });
+                    if let Some(before) = found {
+                        chosen.push(idx);
+                        chosen.extend(before.iter().rev());
+                        return true;
+                    }
+                }
+            }
+        }
+        unreachable!("The numbers reach the result, so some operator leads back to the start.")
+    }
+
+    /// Call `visit` with every choice of operators for the first `count` numbers which can be
+    /// worked out, following on from the operators in `before`, until it breaks.
+    fn evaluations(
+        &self,
+        count: usize,
+        before: &mut Vec<usize>,
+        visit: &mut dyn FnMut(&[usize]) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        self.evaluations_from(self.numbers[0], count, before, visit)
+    }
+
+    fn evaluations_from(
+        &self,
+        value: N,
+        count: usize,
+        before: &mut Vec<usize>,
+        visit: &mut dyn FnMut(&[usize]) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        let next = before.len() + 1;
+        if next == count {
+            return visit(before);
+        }
+        for (idx, operator) in self.operators.iter().enumerate() {
+            if let Some(value) = operator.apply(value, self.numbers[next]) {
+                before.push(idx);
+                let flow = self.evaluations_from(value, count, before, visit);
+                before.pop();
+                flow?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::operator::Builtin;
+
+    const LEFT_TO_RIGHT: Evaluation = Evaluation::LeftToRight;
+
+    /// Every value the equation can be worked out to, the slow way.
+    fn values(numbers: &[u64], operators: &[&dyn Operator], evaluation: Evaluation) -> Vec<u64> {
+        match evaluation {
+            Evaluation::LeftToRight => operators
+                .iter()
+                .zip(&numbers[1..])
+                .try_fold(numbers[0], |lhs, (operator, rhs)| operator.apply(lhs, *rhs))
+                .into_iter()
+                .collect(),
+            Evaluation::Precedence => {
+                // Apply the leftmost of the tightest operators until one number is left.
+                let (mut numbers, mut operators) = (numbers.to_vec(), operators.to_vec());
+                while !operators.is_empty() {
+                    let tightest = operators.iter().map(|op| op.precedence()).max().unwrap();
+                    let idx = operators
+                        .iter()
+                        .position(|op| op.precedence() == tightest)
+                        .unwrap();
+                    let Some(value) = operators[idx].apply(numbers[idx], numbers[idx + 1]) else {
+                        return Vec::new();
+                    };
+                    numbers[idx] = value;
+                    numbers.remove(idx + 1);
+                    operators.remove(idx);
+                }
+                numbers
+            }
+            Evaluation::AnyParentheses => {
+                if operators.is_empty() {
+                    return numbers.to_vec();
+                }
+                let mut found: Vec<u64> = (0..operators.len())
+                    .flat_map(|split| {
+                        let lhs = values(&numbers[..=split], &operators[..split], evaluation);
+                        let rhs =
+                            values(&numbers[split + 1..], &operators[split + 1..], evaluation);
+                        let operator = operators[split];
+                        lhs.into_iter()
+                            .flat_map(|lhs| {
+                                rhs.iter().filter_map(move |rhs| operator.apply(lhs, *rhs))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect();
+                found.sort();
+                found.dedup();
+                found
+            }
+        }
+    }
+
+    /// Write each solution as its operators separated by spaces.
+    fn written<N: Number>(solutions: Vec<Vec<&dyn Operator<N>>>) -> Vec<String> {
+        solutions
+            .iter()
+            .map(|solution| {
+                let symbols: Vec<_> = solution.iter().map(|operator| operator.symbol()).collect();
+                symbols.join(" ")
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case(190, &[10, 19], &["*"])]
+    #[case(3267, &[81, 40, 27], &["+ *", "* +"])]
+    #[case(83, &[17, 5], &[])]
+    #[case(156, &[15, 6], &["||"])]
+    #[case(7290, &[6, 8, 6, 15], &["* || *"])]
+    #[case(161011, &[16, 10, 13], &[])]
+    #[case(192, &[17, 8, 14], &["|| +"])]
+    #[case(21037, &[9, 7, 18, 13], &[])]
+    #[case(292, &[11, 6, 16, 20], &["+ * +"])]
+    #[case(5, &[5], &[""])]
+    #[case(0, &[3, 4, 0], &["+ *", "* *", "|| *"])]
+    fn test_solutions(#[case] result: u64, #[case] numbers: &[u64], #[case] expected: &[&str]) {
+        let operators = OperatorSet::add_multiply_concatenate();
+
+        assert_eq!(
+            written(solutions(result, numbers, &operators, LEFT_TO_RIGHT)),
+            expected
+        );
+        assert_eq!(
+            is_solvable(result, numbers, &operators, LEFT_TO_RIGHT),
+            !expected.is_empty()
+        );
+        assert_eq!(
+            count_solutions(result, numbers, &operators, LEFT_TO_RIGHT),
+            expected.len() as u64
+        );
+        let found = find_solution(result, numbers, &operators, LEFT_TO_RIGHT)
+            .map(|found| written(vec![found]));
+        assert!(found.is_none_or(|found| expected.contains(&found[0].as_str())));
+    }
+
+    #[rstest]
+    #[case("- /", 2, &[10, 3, 7, 4], &[])]
+    #[case("- /", 1, &[10, 3, 7, 0], &["- / -"])]
+    #[case("+ ^", 81, &[1, 2, 2, 2], &["+ ^ ^"])]
+    #[case("* ^", 1, &[2, 3, 0], &["* ^", "^ ^"])]
+    #[case("+ ||2", 0b111, &[1, 1, 0, 1], &["||2 + ||2", "||2 ||2 +"])]
+    #[case("^", 4611686018427387907, &[4611686018427387907, 1], &["^"])]
+    #[case("^ *", 18446744065119617025, &[65535, 65537, 2], &["* ^"])]
+    fn other_operators_can_be_used(
+        #[case] symbols: &str,
+        #[case] result: u64,
+        #[case] numbers: &[u64],
+        #[case] expected: &[&str],
+    ) {
+        let operators: OperatorSet = symbols.parse().unwrap();
+
+        let found = written(solutions(result, numbers, &operators, LEFT_TO_RIGHT));
+
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn custom_operators_are_picked_up() {
+        /// Bitwise exclusive or, which undoes itself.
+        #[derive(Debug)]
+        struct Xor;
+
+        impl Operator for Xor {
+            fn symbol(&self) -> std::borrow::Cow<'_, str> {
+                "xor".into()
+            }
+
+            fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+                Some(lhs ^ rhs)
+            }
+
+            fn undo(&self, result: u64, rhs: u64) -> Undo {
+                Undo::Lhs(result ^ rhs)
+            }
+        }
+
+        let operators = OperatorSet::new().with(Builtin::Add).with(Xor);
+
+        assert_eq!(
+            written(solutions(10, &[4, 5, 3], &operators, LEFT_TO_RIGHT)),
+            ["+ xor"]
+        );
+    }
+
+    #[test]
+    fn long_equations_are_solved_quickly() {
+        // There are 3^59 choices of operators, far too many to try one by one.
+        let numbers = vec![1; 60];
+        let add_multiply = OperatorSet::add_multiply();
+
+        assert!(is_solvable(60, &numbers, &add_multiply, LEFT_TO_RIGHT));
+        assert!(!is_solvable(61, &numbers, &add_multiply, LEFT_TO_RIGHT));
+        assert!(!is_solvable(
+            u64::MAX,
+            &numbers,
+            &OperatorSet::add_multiply_concatenate(),
+            LEFT_TO_RIGHT
+        ));
+        assert_eq!(
+            solutions(60, &numbers, &add_multiply, LEFT_TO_RIGHT).len(),
+            1
+        );
+
+        // Multiplying by 1 changes nothing, so any 29 of the 59 operators can be `+`.
+        let binomial = (1..=29_u128).fold(1, |total, k| total * (30 + k) / k);
+        assert_eq!(
+            count_solutions(30, &numbers, &add_multiply, LEFT_TO_RIGHT) as u128,
+            binomial
+        );
+        assert!(find_solution(30, &numbers, &add_multiply, LEFT_TO_RIGHT).is_some());
+        assert_eq!(
+            count_solutions(50, &[1; 100], &add_multiply, LEFT_TO_RIGHT),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn wide_numbers_can_be_used() {
+        // Adding first goes past `u64::MAX` on the way, which only fits in a `u128`.
+        let operators: OperatorSet = "+ -".parse().unwrap();
+        assert_eq!(
+            written(solutions(
+                u64::MAX,
+                &[u64::MAX, 1, 1],
+                &operators,
+                LEFT_TO_RIGHT
+            )),
+            ["- +"]
+        );
+        let operators: OperatorSet<u128> = "+ -".parse().unwrap();
+        let max = u64::MAX as u128;
+        assert_eq!(
+            written(solutions(max, &[max, 1, 1], &operators, LEFT_TO_RIGHT)),
+            ["+ -", "- +"]
+        );
+
+        // `max * 1 || 7`, and `max || (1 * 7)` with parentheses. With precedence, `||` binding
+        // tightest makes the first `max * 17` instead.
+        let operators = OperatorSet::<u128>::add_multiply_concatenate();
+        for (evaluation, count) in [
+            (Evaluation::LeftToRight, 1),
+            (Evaluation::Precedence, 0),
+            (Evaluation::AnyParentheses, 2),
+        ] {
+            assert_eq!(
+                count_solutions(max * 10 + 7, &[max, 1, 7], &operators, evaluation),
+                count,
+                "{evaluation:?}"
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(170141183460469231731687303715884105731, &[170141183460469231731687303715884105731, 1])]
+    #[case(85070591730234615994970860373908914225, &[9223372036854775815, 2])]
+    #[case(216000000000756000000000882000000000343, &[6000000000007, 3])]
+    fn wide_powers_are_undone_exactly(#[case] result: u128, #[case] numbers: &[u128]) {
+        let operators: OperatorSet<u128> = "^".parse().unwrap();
+
+        for evaluation in [
+            Evaluation::LeftToRight,
+            Evaluation::Precedence,
+            Evaluation::AnyParentheses,
+        ] {
+            assert_eq!(
+                written(solutions(result, numbers, &operators, evaluation)),
+                ["^"],
+                "{evaluation:?}"
+            );
+            assert_eq!(
+                count_solutions(result + 1, numbers, &operators, evaluation),
+                0,
+                "{evaluation:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn values_past_the_result_are_dropped() {
+        // Only adding keeps under the result, as anything else makes a number of at least 6 digits.
+        let operators: OperatorSet = OperatorSet::add_multiply_concatenate();
+
+        for (evaluation, count) in [
+            (Evaluation::Precedence, 40),
+            (Evaluation::AnyParentheses, 8),
+        ] {
+            let numbers = vec![999; count];
+            assert_eq!(
+                count_solutions(999 * count as u64, &numbers, &operators, evaluation),
+                1,
+                "{evaluation:?}"
+            );
+        }
+    }
+
+    #[rstest]
+    #[case("+ - * / ^ ||")]
+    #[case("+ * ||")]
+    fn agrees_with_trying_every_choice(#[case] symbols: &str) {
+        // A small linear congruential generator, to get the same equations every time.
+        let mut seed = 7_u64;
+        let mut next = |below: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) % below
+        };
+        let operators: OperatorSet = symbols.parse().unwrap();
+        let all: Vec<&dyn Operator> = operators.iter().collect();
+
+        for round in 0..600 {
+            let evaluation = [
+                Evaluation::LeftToRight,
+                Evaluation::Precedence,
+                Evaluation::AnyParentheses,
+            ][round % 3];
+            let numbers: Vec<u64> = (0..next(5) + 1).map(|_| next(12)).collect();
+            let choices: Vec<Vec<&dyn Operator>> = (0..all.len().pow(numbers.len() as u32 - 1))
+                .map(|mut idx| {
+                    (1..numbers.len())
+                        .map(|_| {
+                            let operator = all[idx % all.len()];
+                            idx /= all.len();
+                            operator
+                        })
+                        .collect()
+                })
+                .collect();
+            let pick = &choices[next(choices.len() as u64) as usize];
+            let Some(result) = values(&numbers, pick, evaluation).first().copied() else {
+                continue;
+            };
+
+            let mut expected: Vec<String> = written(
+                choices
+                    .into_iter()
+                    .filter(|choice| values(&numbers, choice, evaluation).contains(&result))
+                    .collect(),
+            );
+            expected.sort();
+            let mut found = written(solutions(result, &numbers, &operators, evaluation));
+            found.sort();
+            let one = find_solution(result, &numbers, &operators, evaluation).unwrap();
+
+            assert_eq!(found, expected, "{result} from {numbers:?}, {evaluation:?}");
+            assert_eq!(
+                count_solutions(result, &numbers, &operators, evaluation),
+                expected.len() as u64
+            );
+            assert!(is_solvable(result, &numbers, &operators, evaluation));
+            assert!(expected.contains(&written(vec![one.clone()])[0]));
+            if evaluation == Evaluation::AnyParentheses {
+                assert!(parenthesize(result, &numbers, &one).is_some());
+            }
+        }
+    }
+}
@@ -1,13 +1,15 @@
 use std::collections::{HashMap, HashSet};
 
-use glam::IVec2;
+use aoc_common::SolveError;
+use aoc_grid::{Grid, IVec2};
 
 pub fn process(input: &str) -> usize {
-    // Get size of grid.
-    let y = input.lines().filter(|line| !line.is_empty()).count();
-    let x = input.lines().next().unwrap().chars().count();
+    try_process(input).expect("The input should be a rectangular grid.")
+}
 
-    let antennas = parse_grid_manual(input);
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
+    let grid = Grid::parse(input)?;
+    let antennas = find_antennas(&grid);
 
     let sorted_antennas = sort_antennas(&antennas);
     let antinodes = find_antinodes(&sorted_antennas);
@@ -15,7 +17,7 @@ pub fn process(input: &str) -> usize {
     // Filter out antinodes that are outside the grid.
     let antinodes_with_char = antinodes
         .into_iter()
-        .filter(|(_, pos)| (0..x).contains(&(pos.x as usize)) && (0..y).contains(&(pos.y as usize)))
+        .filter(|(_, pos)| grid.contains(*pos))
         .collect::<HashSet<_>>();
 
     // Filter out any overlapping antinodes.
@@ -24,19 +26,14 @@ pub fn process(input: &str) -> usize {
         .map(|(_, pos)| *pos)
         .collect::<HashSet<_>>();
 
-    antinodes.len()
+    Ok(antinodes.len())
 }
 
-fn parse_grid_manual(input: &str) -> HashMap<IVec2, char> {
-    let mut grid = HashMap::new();
-    for (y, line) in input.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            if ch.is_alphanumeric() {
-                grid.insert(IVec2::new(x as i32, y as i32), ch);
-            }
-        }
-    }
-    grid
+fn find_antennas(grid: &Grid<char>) -> HashMap<IVec2, char> {
+    grid.iter()
+        .filter(|(_, ch)| ch.is_alphanumeric())
+        .map(|(pos, ch)| (pos, *ch))
+        .collect()
 }
 
 fn sort_antennas(antennas: &HashMap<IVec2, char>) -> HashMap<char, Vec<IVec2>> {
@@ -99,6 +96,16 @@ mod tests {
         assert_eq!(process(input), 14);
     }
 
+    #[rstest]
+    #[case("", Err(SolveError::EmptyInput))]
+    #[case("a...\n...\n", Err(SolveError::Malformed {
+        location: aoc_common::Location::new(2, 1),
+        reason: "expected a row of 4 characters, found 3".to_string(),
+    }))]
+    fn test_try_process(#[case] input: &str, #[case] expected: Result<usize, SolveError>) {
+        assert_eq!(try_process(input), expected);
+    }
+
     #[rstest]
     #[case("............", HashMap::new())]
     #[case(
@@ -116,7 +123,7 @@ mod tests {
         ].iter().cloned().collect()
     )]
     fn test_parse_grid(#[case] input: &str, #[case] expected: HashMap<IVec2, char>) {
-        let res = parse_grid_manual(input);
+        let res = find_antennas(&Grid::parse(input).unwrap());
         assert_eq!(res, expected, "Expected: {:#?}, Got: {:#?}", expected, res);
     }
 
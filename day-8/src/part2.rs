@@ -1,16 +1,18 @@
 use std::collections::{HashMap, HashSet};
 
-use glam::IVec2;
+use aoc_common::SolveError;
+use aoc_grid::{Grid, IVec2};
 
 pub fn process(input: &str) -> usize {
-    // Get size of grid.
-    let y = input.lines().filter(|line| !line.is_empty()).count();
-    let x = input.lines().next().unwrap().chars().count();
+    try_process(input).expect("The input should be a rectangular grid.")
+}
 
-    let antennas = parse_grid_manual(input);
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
+    let grid = Grid::parse(input)?;
+    let antennas = find_antennas(&grid);
 
     let sorted_antennas = sort_antennas(&antennas);
-    let antinodes = find_antinodes(&sorted_antennas, (x as i32, y as i32));
+    let antinodes = find_antinodes(&sorted_antennas, &grid);
 
     // Filter out any overlapping antinodes.
     let antinodes = antinodes
@@ -18,19 +20,14 @@ pub fn process(input: &str) -> usize {
         .map(|(_, pos)| *pos)
         .collect::<HashSet<_>>();
 
-    antinodes.len()
+    Ok(antinodes.len())
 }
 
-fn parse_grid_manual(input: &str) -> HashMap<IVec2, char> {
-    let mut grid = HashMap::new();
-    for (y, line) in input.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            if ch.is_alphanumeric() {
-                grid.insert(IVec2::new(x as i32, y as i32), ch);
-            }
-        }
-    }
-    grid
+fn find_antennas(grid: &Grid<char>) -> HashMap<IVec2, char> {
+    grid.iter()
+        .filter(|(_, ch)| ch.is_alphanumeric())
+        .map(|(pos, ch)| (pos, *ch))
+        .collect()
 }
 
 fn sort_antennas(antennas: &HashMap<IVec2, char>) -> HashMap<char, Vec<IVec2>> {
@@ -48,11 +45,9 @@ fn sort_antennas(antennas: &HashMap<IVec2, char>) -> HashMap<char, Vec<IVec2>> {
 ///
 /// NOTE:
 /// This function now DOES check if the antinode is within the grid.
-/// The `x_limit` and `y_limit` parameters are the maximum x and y values of the grid.
-/// It is assumed that the grid starts at (0, 0).
 fn find_antinodes(
     antennas: &HashMap<char, Vec<IVec2>>,
-    (x_limit, y_limit): (i32, i32),
+    grid: &Grid<char>,
 ) -> HashSet<(char, IVec2)> {
     let mut antinodes = HashSet::new();
 
@@ -77,9 +72,7 @@ fn find_antinodes(
                 // First in one direction.
                 let antinode_diff1 = pos1 - pos2;
                 let mut start_pos1 = pos1 + antinode_diff1;
-                while (0..x_limit).contains(&{ start_pos1.x })
-                    && (0..y_limit).contains(&{ start_pos1.y })
-                {
+                while grid.contains(start_pos1) {
                     antinodes.insert((*ch, start_pos1));
                     start_pos1 += antinode_diff1;
                 }
@@ -87,9 +80,7 @@ fn find_antinodes(
                 // Now in the other direction.
                 let antinode_diff2 = pos2 - pos1;
                 let mut start_pos2 = pos2 + antinode_diff2;
-                while (0..x_limit).contains(&{ start_pos2.x })
-                    && (0..y_limit).contains(&{ start_pos2.y })
-                {
+                while grid.contains(start_pos2) {
                     antinodes.insert((*ch, start_pos2));
                     start_pos2 += antinode_diff2;
                 }
@@ -123,6 +114,16 @@ mod tests {
         assert_eq!(process(input), 34);
     }
 
+    #[rstest]
+    #[case("", Err(SolveError::EmptyInput))]
+    #[case("a...\n...\n", Err(SolveError::Malformed {
+        location: aoc_common::Location::new(2, 1),
+        reason: "expected a row of 4 characters, found 3".to_string(),
+    }))]
+    fn test_try_process(#[case] input: &str, #[case] expected: Result<usize, SolveError>) {
+        assert_eq!(try_process(input), expected);
+    }
+
     #[rstest]
     #[case("............", HashMap::new())]
     #[case(
@@ -140,7 +141,7 @@ mod tests {
         ].iter().cloned().collect()
     )]
     fn test_parse_grid(#[case] input: &str, #[case] expected: HashMap<IVec2, char>) {
-        let res = parse_grid_manual(input);
+        let res = find_antennas(&Grid::parse(input).unwrap());
         assert_eq!(res, expected, "Expected: {:#?}, Got: {:#?}", expected, res);
     }
 
@@ -194,9 +195,9 @@ mod tests {
     fn test_find_antinodes(
         #[case] antennas: HashMap<char, Vec<IVec2>>,
         #[case] expected: HashSet<(char, IVec2)>,
-        #[case] (x_limit, y_limit): (i32, i32),
+        #[case] (width, height): (usize, usize),
     ) {
-        let result = find_antinodes(&antennas, (x_limit, y_limit));
+        let result = find_antinodes(&antennas, &Grid::new(width, height, '.'));
 
         assert_eq!(
             result, expected,
@@ -205,4 +206,3 @@ mod tests {
         );
     }
 }
-
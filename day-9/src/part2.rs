@@ -1,3 +1,5 @@
+use aoc_common::SolveError;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct DiskSegment {
     capacity: usize,
@@ -37,6 +39,7 @@ impl DiskSegment {
     /// Pop a file slice from a segment.
     ///
     /// the free_space segment is popped first, then the file.
+    #[allow(dead_code)]
     fn pop_file_slice(&mut self) -> Option<usize> {
         if self.fill_level == 0 {
             return None;
@@ -54,17 +57,22 @@ impl DiskSegment {
     }
 }
 
-fn parse_line_segments(input: &str) -> Vec<DiskSegment> {
+fn parse_line_segments(input: &str) -> Result<Vec<DiskSegment>, SolveError> {
+    let disk_map = input.trim();
+    if disk_map.is_empty() {
+        return Err(SolveError::EmptyInput);
+    }
+
     // Collect all pairs into Vec<(u32, u32)>.
     // If we have an odd number of elements, we should add a 0 to the end.
     // We should then create a DiskSegment for each pair of elements.
-    let pairs: Vec<(u32, u32)> = input
-        .chars()
-        .map(|c| {
+    let pairs: Vec<(u32, u32)> = disk_map
+        .char_indices()
+        .map(|(idx, c)| {
             c.to_digit(10)
-                .expect("We should always be able to parse numbers.")
+                .ok_or_else(|| SolveError::unexpected(input, &disk_map[idx..], "a digit"))
         })
-        .collect::<Vec<u32>>()
+        .collect::<Result<Vec<u32>, _>>()?
         .chunks(2)
         .map(|chunk| {
             if chunk.len() == 1 {
@@ -81,11 +89,15 @@ fn parse_line_segments(input: &str) -> Vec<DiskSegment> {
         .map(|(idx, pair)| DiskSegment::new(idx, pair.0 as usize, pair.1 as usize))
         .collect();
 
-    segments
+    Ok(segments)
 }
 
 pub fn process(input: &str) -> usize {
-    let segments = parse_line_segments(input.trim());
+    try_process(input).expect("The input should be a single line of digits.")
+}
+
+pub fn try_process(input: &str) -> Result<usize, SolveError> {
+    let segments = parse_line_segments(input)?;
     let mut new_disk = segments.clone();
     let mut files_to_nuke = vec![];
 
@@ -175,7 +187,7 @@ pub fn process(input: &str) -> usize {
         disk_index += 1;
     }
 
-    checksum
+    Ok(checksum)
 }
 
 #[cfg(test)]
@@ -230,7 +242,18 @@ mod tests {
     )]
     fn test_parse_line_segments(#[case] input: &str, #[case] expected: Vec<DiskSegment>) {
         let segments = parse_line_segments(input);
-        assert_eq!(segments, expected);
+        assert_eq!(segments, Ok(expected));
     }
-}
 
+    #[rstest]
+    #[case("", SolveError::EmptyInput)]
+    #[case("\n", SolveError::EmptyInput)]
+    #[case("2333x33", SolveError::UnexpectedChar {
+        location: aoc_common::Location::new(1, 5),
+        found: 'x',
+        expected: "a digit",
+    })]
+    fn try_process_reports_invalid_disk_maps(#[case] input: &str, #[case] expected: SolveError) {
+        assert_eq!(try_process(input), Err(expected));
+    }
+}